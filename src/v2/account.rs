@@ -7,9 +7,9 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct NewAccount {
-    /// Array of URLs that can be used by the ACME provider to contact the client
+    /// Array of URIs that can be used by the ACME provider to contact the client
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
-    pub contact: Option<String>,
+    pub contact: Option<Vec<super::Contact>>,
     /// Confirmation client has agreed to the ACME provider's Terms of Service
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
     #[cfg_attr(feature = "json", serde(rename = "termsOfServiceAgreed"))]
@@ -47,7 +47,7 @@ pub struct AccountUpdate {
     ///
     /// For more information, refer to [RFC 8555 § 7.3.2](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3.2)
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
-    pub contact: Option<Vec<String>>,
+    pub contact: Option<Vec<super::Contact>>,
     /// This field should not be set unless deactivating the ACME client.
     ///
     /// For more information, refer to [RFC 8555 § 7.3.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3.6)
@@ -82,9 +82,9 @@ impl AccountUpdate {
 pub struct Account {
     /// ACME account status
     pub status: AccountStatus,
-    /// Array of URLs that can be used by the ACME provider to contact the client
+    /// Array of URIs that can be used by the ACME provider to contact the client
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
-    pub contact: Option<Vec<String>>,
+    pub contact: Option<Vec<super::Contact>>,
     /// If set to true, indicates that the ACME account has agreed to the ACME provider's Terms of Service
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
     #[cfg_attr(feature = "json", serde(rename = "termsOfServiceAgreed"))]
@@ -96,7 +96,7 @@ pub struct Account {
     /// URL from which a list of orders submitted by the ACME account can be retrieved.
     ///
     /// For more information, refer to [RFC 8555 § 7.1.2.1](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.2.1)
-    pub orders: String,
+    pub orders: super::AccountOrdersUrl,
 }
 
 #[cfg(feature = "json")]
@@ -112,6 +112,33 @@ impl Account {
     }
 }
 
+/// Defines an ACME account key change object, i.e. the payload of the inner JSON web signature
+/// of a key change request.
+///
+/// For more information, refer to [RFC 8555 § 7.3.5](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3.5)
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct KeyChange {
+    /// URL of the ACME account whose key is being changed
+    pub account: super::AccountUrl,
+    /// Current key of the ACME account
+    #[cfg_attr(feature = "json", serde(rename = "oldKey"))]
+    pub old_key: super::JsonWebKey,
+}
+
+#[cfg(feature = "json")]
+impl KeyChange {
+    /// Deserializes a KeyChange object from a JSON str
+    pub fn from_str(s: &str) -> Result<KeyChange, serde_json::error::Error> {
+        serde_json::from_str(s)
+    }
+
+    /// Serializes a KeyChange object to a JSON String
+    pub fn to_string(&self) -> Result<String, serde_json::error::Error> {
+        serde_json::to_string(self)
+    }
+}
+
 /// Defines an ACME account orders object.
 ///
 /// For more information, refer to [RFC 8555 § 7.1.2.1](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.2.1)
@@ -119,7 +146,7 @@ impl Account {
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct AccountOrders {
     /// Array of URLs identifying orders belonging to the ACME account
-    pub orders: Vec<String>,
+    pub orders: Vec<super::OrderUrl>,
 }
 
 #[cfg(feature = "json")]
@@ -135,10 +162,139 @@ impl AccountOrders {
     }
 }
 
+impl AccountOrders {
+    /// Splits an account's orders into pages of `page_size` orders and returns the requested
+    /// (zero-based) page, linking to the next page using `page_url`
+    ///
+    /// Returns `None` if the page does not exist; the first page always exists, even if empty.
+    pub fn paginate(
+        orders: &[super::OrderUrl],
+        page: usize,
+        page_size: std::num::NonZeroUsize,
+        page_url: impl Fn(usize) -> super::AccountOrdersUrl,
+    ) -> Option<AccountOrdersPage> {
+        let page_size = page_size.get();
+        let start = page.checked_mul(page_size)?;
+        if page > 0 && start >= orders.len() {
+            return None;
+        }
+
+        let end = orders.len().min(start.saturating_add(page_size));
+
+        Some(AccountOrdersPage {
+            orders: AccountOrders {
+                orders: orders.get(start..end).unwrap_or_default().to_vec(),
+            },
+            next: match end < orders.len() {
+                true => Some(page_url(page + 1)),
+                false => None,
+            },
+        })
+    }
+}
+
+/// Defines a page of an ACME account orders list, along with the URL of the next page, which is
+/// sent in a `Link` header field with the `next` relation
+///
+/// For more information, refer to [RFC 8555 § 7.1.2.1](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.2.1)
+#[derive(Clone, Debug)]
+pub struct AccountOrdersPage {
+    /// Orders of the page
+    pub orders: AccountOrders,
+    /// URL of the next page, if any
+    pub next: Option<super::AccountOrdersUrl>,
+}
+
+impl AccountOrdersPage {
+    /// Combines an account orders object with the `Link` header fields of its response
+    pub fn new(orders: AccountOrders, links: &[super::Link]) -> AccountOrdersPage {
+        AccountOrdersPage {
+            orders,
+            next: super::Link::find(links, &super::LinkRelation::Next)
+                .map(super::AccountOrdersUrl::new),
+        }
+    }
+
+    /// Returns the `Link` header fields to be sent with the page
+    pub fn links(&self) -> Vec<super::Link> {
+        self.next
+            .iter()
+            .map(|url| super::Link::new(url.as_str(), super::LinkRelation::Next))
+            .collect()
+    }
+}
+
+/// Iterates over the order URLs of an ACME account, fetching the pages of its orders list lazily
+/// using a caller-supplied function
+///
+/// Iteration stops after the last page, after the first error, or if a page links back to a
+/// page which has already been fetched.
+///
+/// For more information, refer to [RFC 8555 § 7.1.2.1](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.2.1)
+pub struct AccountOrdersIter<F> {
+    fetch: F,
+    next: Option<super::AccountOrdersUrl>,
+    fetched: std::collections::HashSet<super::AccountOrdersUrl>,
+    orders: std::vec::IntoIter<super::OrderUrl>,
+}
+
+impl<F, E> AccountOrdersIter<F>
+where
+    F: FnMut(&super::AccountOrdersUrl) -> Result<AccountOrdersPage, E>,
+{
+    /// Creates an iterator starting at the account's `orders` URL, which fetches each page using
+    /// `fetch`
+    pub fn new(url: super::AccountOrdersUrl, fetch: F) -> AccountOrdersIter<F> {
+        AccountOrdersIter {
+            fetch,
+            next: Some(url),
+            fetched: std::collections::HashSet::new(),
+            orders: vec![].into_iter(),
+        }
+    }
+}
+
+impl<F, E> Iterator for AccountOrdersIter<F>
+where
+    F: FnMut(&super::AccountOrdersUrl) -> Result<AccountOrdersPage, E>,
+{
+    type Item = Result<super::OrderUrl, E>;
+
+    fn next(&mut self) -> Option<Result<super::OrderUrl, E>> {
+        loop {
+            if let Some(order) = self.orders.next() {
+                return Some(Ok(order));
+            }
+
+            let url = self.next.take()?;
+            if !self.fetched.insert(url.clone()) {
+                return None;
+            }
+
+            match (self.fetch)(&url) {
+                Ok(page) => {
+                    self.orders = page.orders.orders.into_iter();
+                    self.next = page.next;
+                }
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+impl<F> std::fmt::Debug for AccountOrdersIter<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccountOrdersIter")
+            .field("next", &self.next)
+            .field("orders", &self.orders)
+            .finish_non_exhaustive()
+    }
+}
+
 /// Account resource status values
 ///
 /// For more information, refer to [RFC 8555 § 7.1.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.6)
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum AccountStatus {
     #[cfg_attr(feature = "json", serde(rename = "valid"))]
@@ -148,3 +304,181 @@ pub enum AccountStatus {
     #[cfg_attr(feature = "json", serde(rename = "revoked"))]
     Revoked,
 }
+
+impl AccountStatus {
+    /// Returns the account status after the event, or an error if the RFC 8555 state diagram does
+    /// not allow the event in the current status
+    ///
+    /// For more information, refer to [RFC 8555 § 7.1.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.6)
+    pub fn transition(
+        self,
+        event: AccountEvent,
+    ) -> Result<AccountStatus, super::StatusTransitionError> {
+        match (self, event) {
+            (AccountStatus::Valid, AccountEvent::Deactivate) => Ok(AccountStatus::Deactivated),
+            (AccountStatus::Valid, AccountEvent::Revoke) => Ok(AccountStatus::Revoked),
+            (status, event) => Err(super::StatusTransitionError::Account(status, event)),
+        }
+    }
+
+    /// Returns true if the account can no longer be used, i.e. its status is `deactivated` or
+    /// `revoked`
+    pub fn is_final(&self) -> bool {
+        self.is_terminal()
+    }
+
+    /// Returns true if no event can change the account status
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, AccountStatus::Deactivated | AccountStatus::Revoked)
+    }
+
+    /// Returns the status value used in the account resource
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountStatus::Valid => "valid",
+            AccountStatus::Deactivated => "deactivated",
+            AccountStatus::Revoked => "revoked",
+        }
+    }
+}
+
+/// Account resource status transition events
+///
+/// For more information, refer to [RFC 8555 § 7.1.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.6)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AccountEvent {
+    /// The ACME client deactivated the account (`valid` to `deactivated`)
+    Deactivate,
+    /// The ACME provider revoked the account (`valid` to `revoked`)
+    Revoke,
+}
+
+impl AccountEvent {
+    /// Returns a short description of the event
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountEvent::Deactivate => "deactivate",
+            AccountEvent::Revoke => "revoke",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::*;
+    use crate::v2::{AccountOrdersUrl, OrderUrl};
+
+    fn orders(count: usize) -> Vec<OrderUrl> {
+        (0..count)
+            .map(|order| OrderUrl::new(format!("https://example.com/acme/order/{}", order)))
+            .collect()
+    }
+
+    fn page_url(page: usize) -> AccountOrdersUrl {
+        AccountOrdersUrl::new(format!("https://example.com/acme/orders?page={}", page))
+    }
+
+    fn paginate(orders: &[OrderUrl], page: usize) -> Option<AccountOrdersPage> {
+        AccountOrders::paginate(orders, page, NonZeroUsize::new(2).unwrap(), page_url)
+    }
+
+    #[test]
+    fn paginate_pages() {
+        let orders = orders(5);
+
+        let first = paginate(&orders, 0).unwrap();
+        assert_eq!(first.orders.orders, orders[0..2]);
+        assert_eq!(first.next, Some(page_url(1)));
+        assert_eq!(
+            first.links(),
+            vec![crate::v2::Link::new(
+                page_url(1).as_str(),
+                crate::v2::LinkRelation::Next
+            )]
+        );
+
+        let second = paginate(&orders, 1).unwrap();
+        assert_eq!(second.orders.orders, orders[2..4]);
+        assert_eq!(second.next, Some(page_url(2)));
+
+        let last = paginate(&orders, 2).unwrap();
+        assert_eq!(last.orders.orders, orders[4..]);
+        assert_eq!(last.next, None);
+        assert!(last.links().is_empty());
+
+        assert!(paginate(&orders, 3).is_none());
+        assert!(paginate(&orders, usize::MAX).is_none());
+        assert!(paginate(&orders[..4], 2).is_none());
+
+        let empty = paginate(&[], 0).unwrap();
+        assert!(empty.orders.orders.is_empty());
+        assert_eq!(empty.next, None);
+        assert!(paginate(&[], 1).is_none());
+    }
+
+    #[test]
+    fn iterate_pages() {
+        let orders = orders(5);
+        let mut fetched = vec![];
+        let mut iter = AccountOrdersIter::new(page_url(0), |url: &AccountOrdersUrl| {
+            fetched.push(url.clone());
+            let page = (0..)
+                .find(|page| &page_url(*page) == url)
+                .and_then(|page| paginate(&orders, page));
+            page.ok_or("not found")
+        });
+
+        assert_eq!(iter.next(), Some(Ok(orders[0].clone())));
+        assert_eq!(iter.next(), Some(Ok(orders[1].clone())));
+        assert_eq!(iter.next(), Some(Ok(orders[2].clone())));
+        assert_eq!(iter.by_ref().count(), 2);
+        assert_eq!(iter.next(), None);
+        drop(iter);
+        assert_eq!(fetched, vec![page_url(0), page_url(1), page_url(2)]);
+    }
+
+    #[test]
+    fn iterate_fetch_error() {
+        let orders = orders(3);
+        let iter = AccountOrdersIter::new(page_url(0), |url: &AccountOrdersUrl| {
+            match url == &page_url(0) {
+                true => Ok(paginate(&orders, 0).unwrap()),
+                false => Err("unavailable"),
+            }
+        });
+
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            vec![
+                Ok(orders[0].clone()),
+                Ok(orders[1].clone()),
+                Err("unavailable")
+            ]
+        );
+    }
+
+    #[test]
+    fn iterate_cycle() {
+        let orders = orders(2);
+        let mut fetches = 0;
+        let iter = AccountOrdersIter::new(page_url(0), |url: &AccountOrdersUrl| {
+            fetches += 1;
+            // Each page links back to the other, so that the orders would be yielded forever
+            let next = match url == &page_url(0) {
+                true => page_url(1),
+                false => page_url(0),
+            };
+            Ok::<_, ()>(AccountOrdersPage {
+                orders: AccountOrders {
+                    orders: orders.clone(),
+                },
+                next: Some(next),
+            })
+        });
+
+        assert_eq!(iter.map(Result::unwrap).count(), 4);
+        assert_eq!(fetches, 2);
+    }
+}
@@ -27,7 +27,7 @@ pub struct Authorization {
     /// Authorization status
     pub status: AuthorizationStatus,
     /// Authorization expiration time
-    pub expires: Option<String>,
+    pub expires: Option<super::Timestamp>,
     /// Authorization challenge objects
     pub challenges: Vec<Challenge>,
     /// Present and true for authorizations for a domain name containing a wildcard
@@ -47,6 +47,29 @@ impl Authorization {
     }
 }
 
+#[cfg(feature = "time")]
+impl Authorization {
+    /// Returns the expiration time, or `None` if the authorization has none or it is not a valid RFC 3339
+    /// date-time
+    pub fn expires_at(&self) -> Option<time::OffsetDateTime> {
+        self.expires
+            .as_ref()
+            .and_then(super::Timestamp::to_date_time)
+    }
+
+    /// Returns true if the authorization has an expiration time and it has passed as of `now`
+    pub fn is_expired(&self, now: time::OffsetDateTime) -> bool {
+        self.expires_at().is_some_and(|expires| expires <= now)
+    }
+
+    /// Returns the time remaining until the authorization expires, which is negative once it has expired
+    ///
+    /// Returns `None` if the authorization has no expiration time.
+    pub fn expires_in(&self, now: time::OffsetDateTime) -> Option<time::Duration> {
+        self.expires_at().map(|expires| expires - now)
+    }
+}
+
 /// Defines an update to an Authorization resource.
 ///
 /// This can be used when the ACME client wishes to relinquish authorization to issue certificates for an identifier.
@@ -79,7 +102,7 @@ impl AuthorizationUpdate {
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Challenge {
     /// URL to respond to challenge
-    pub url: String,
+    pub url: super::ChallengeUrl,
     /// Challenge type
     #[cfg_attr(feature = "json", serde(rename = "type"))]
     pub type_: ChallengeType,
@@ -90,7 +113,7 @@ pub struct Challenge {
     /// For more information, refer to [RFC 8555 § 8.1](https://datatracker.ietf.org/doc/html/rfc8555#section-8.1)
     pub token: Option<String>,
     /// Time at which the challenge was validated
-    pub validated: Option<String>,
+    pub validated: Option<super::Timestamp>,
     /// Error(s) encountered during challenge validation
     pub error: Option<super::Error>,
 }
@@ -108,10 +131,72 @@ impl Challenge {
     }
 }
 
+impl Challenge {
+    /// Returns the path at which the ACME provider requests the http-01 key authorization,
+    /// i.e. `/.well-known/acme-challenge/{token}`
+    ///
+    /// For more information, refer to [RFC 8555 § 8.3](https://datatracker.ietf.org/doc/html/rfc8555#section-8.3)
+    pub fn http01_path(&self) -> Option<String> {
+        self.token
+            .as_ref()
+            .map(|token| format!("/.well-known/acme-challenge/{}", token))
+    }
+}
+
+#[cfg(feature = "time")]
+impl Challenge {
+    /// Returns the validation time, or `None` if the challenge has none or it is not a valid
+    /// RFC 3339 date-time
+    pub fn validated_at(&self) -> Option<time::OffsetDateTime> {
+        self.validated
+            .as_ref()
+            .and_then(super::Timestamp::to_date_time)
+    }
+}
+
+#[cfg(feature = "crypto")]
+impl Challenge {
+    /// Computes the key authorization for the challenge, i.e. `token || '.' || thumbprint(key)`
+    ///
+    /// Returns `None` if the challenge has no token.
+    ///
+    /// For more information, refer to [RFC 8555 § 8.1](https://datatracker.ietf.org/doc/html/rfc8555#section-8.1)
+    pub fn key_authorization(&self, key: &super::JsonWebKey) -> Option<String> {
+        self.token
+            .as_ref()
+            .map(|token| format!("{}.{}", token, key.thumbprint()))
+    }
+
+    /// Computes the body of the HTTP response to an http-01 validation request, i.e. the key
+    /// authorization
+    ///
+    /// For more information, refer to [RFC 8555 § 8.3](https://datatracker.ietf.org/doc/html/rfc8555#section-8.3)
+    pub fn http01_response_body(&self, key: &super::JsonWebKey) -> Option<String> {
+        self.key_authorization(key)
+    }
+
+    /// Computes the value of the `_acme-challenge` TXT record for a dns-01 challenge
+    ///
+    /// For more information, refer to [RFC 8555 § 8.4](https://datatracker.ietf.org/doc/html/rfc8555#section-8.4)
+    pub fn dns01_txt_record(&self, key: &super::JsonWebKey) -> Option<String> {
+        self.key_authorization(key)
+            .map(|key_authorization| dns01_txt_record(&key_authorization))
+    }
+
+    /// Computes the SHA-256 digest of the key authorization carried in the `acmeIdentifier`
+    /// extension for a tls-alpn-01 challenge
+    ///
+    /// For more information, refer to [RFC 8737 § 3](https://datatracker.ietf.org/doc/html/rfc8737#section-3)
+    pub fn tls_alpn01_digest(&self, key: &super::JsonWebKey) -> Option<[u8; 32]> {
+        self.key_authorization(key)
+            .map(|key_authorization| tls_alpn01_digest(&key_authorization))
+    }
+}
+
 /// Challenge resource status values
 ///
 /// For more information, refer to [RFC 8555 § 7.1.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.6)
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum ChallengeStatus {
     #[cfg_attr(feature = "json", serde(rename = "pending"))]
@@ -124,24 +209,174 @@ pub enum ChallengeStatus {
     Invalid,
 }
 
+impl ChallengeStatus {
+    /// Returns the challenge status after the event, or an error if the RFC 8555 state diagram
+    /// does not allow the event in the current status
+    ///
+    /// For more information, refer to [RFC 8555 § 7.1.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.6)
+    pub fn transition(
+        self,
+        event: ChallengeEvent,
+    ) -> Result<ChallengeStatus, super::StatusTransitionError> {
+        match (self, event) {
+            (ChallengeStatus::Pending, ChallengeEvent::Respond) => Ok(ChallengeStatus::Processing),
+            (ChallengeStatus::Processing, ChallengeEvent::Retry) => Ok(ChallengeStatus::Processing),
+            (ChallengeStatus::Processing, ChallengeEvent::Validate) => Ok(ChallengeStatus::Valid),
+            (ChallengeStatus::Processing, ChallengeEvent::Fail) => Ok(ChallengeStatus::Invalid),
+            (status, event) => Err(super::StatusTransitionError::Challenge(status, event)),
+        }
+    }
+
+    /// Returns true if the challenge has been validated, i.e. its status is `valid` or `invalid`
+    pub fn is_final(&self) -> bool {
+        self.is_terminal()
+    }
+
+    /// Returns true if no event can change the challenge status
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, ChallengeStatus::Valid | ChallengeStatus::Invalid)
+    }
+
+    /// Returns the status value used in the challenge resource
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChallengeStatus::Pending => "pending",
+            ChallengeStatus::Processing => "processing",
+            ChallengeStatus::Valid => "valid",
+            ChallengeStatus::Invalid => "invalid",
+        }
+    }
+}
+
+/// Challenge resource status transition events
+///
+/// For more information, refer to [RFC 8555 § 7.1.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.6)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChallengeEvent {
+    /// The ACME client responded to the challenge (`pending` to `processing`)
+    Respond,
+    /// Validation failed and the ACME provider will retry it (`processing` to `processing`)
+    Retry,
+    /// The ACME provider validated the challenge (`processing` to `valid`)
+    Validate,
+    /// Validation failed or the maximum number of retries was reached (`processing` to `invalid`)
+    Fail,
+}
+
+impl ChallengeEvent {
+    /// Returns a short description of the event
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChallengeEvent::Respond => "respond",
+            ChallengeEvent::Retry => "retry",
+            ChallengeEvent::Validate => "validate",
+            ChallengeEvent::Fail => "fail",
+        }
+    }
+}
+
 /// Challenge resource type values
 ///
 /// For more information, refer to [RFC 8555 § 8](https://datatracker.ietf.org/doc/html/rfc8555#section-8)
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json", serde(from = "String", into = "String"))]
 pub enum ChallengeType {
     /// For more information about http-01 challenges, refer to [RFC 8555 § 8.3](https://datatracker.ietf.org/doc/html/rfc8555#section-8.3)
-    #[cfg_attr(feature = "json", serde(rename = "http-01"))]
     Http01,
     /// For more information about dns-01 challenges, refer to [RFC 8555 § 8.4](https://datatracker.ietf.org/doc/html/rfc8555#section-8.4)
-    #[cfg_attr(feature = "json", serde(rename = "dns-01"))]
     Dns01,
+    /// For more information about tls-alpn-01 challenges, refer to [RFC 8737 § 3](https://datatracker.ietf.org/doc/html/rfc8737#section-3)
+    TlsAlpn01,
+    /// Challenge type not known to this crate
+    Unknown(String),
+}
+
+impl ChallengeType {
+    /// Returns the challenge type name, e.g. `http-01`
+    pub fn as_str(&self) -> &str {
+        match self {
+            ChallengeType::Http01 => "http-01",
+            ChallengeType::Dns01 => "dns-01",
+            ChallengeType::TlsAlpn01 => "tls-alpn-01",
+            ChallengeType::Unknown(s) => s,
+        }
+    }
+}
+
+impl From<&str> for ChallengeType {
+    fn from(s: &str) -> ChallengeType {
+        match s {
+            "http-01" => ChallengeType::Http01,
+            "dns-01" => ChallengeType::Dns01,
+            "tls-alpn-01" => ChallengeType::TlsAlpn01,
+            _ => ChallengeType::Unknown(s.to_string()),
+        }
+    }
+}
+
+impl From<String> for ChallengeType {
+    fn from(s: String) -> ChallengeType {
+        ChallengeType::from(s.as_str())
+    }
+}
+
+impl From<ChallengeType> for String {
+    fn from(type_: ChallengeType) -> String {
+        match type_ {
+            ChallengeType::Unknown(s) => s,
+            _ => type_.as_str().to_string(),
+        }
+    }
+}
+
+/// ALPN protocol name negotiated by the ACME provider when performing tls-alpn-01 validation
+///
+/// For more information, refer to [RFC 8737 § 6.2](https://datatracker.ietf.org/doc/html/rfc8737#section-6.2)
+pub const TLS_ALPN01_PROTOCOL: &[u8] = b"acme-tls/1";
+
+/// Object identifier of the `acmeIdentifier` X.509 extension used by tls-alpn-01 challenges
+///
+/// For more information, refer to [RFC 8737 § 6.1](https://datatracker.ietf.org/doc/html/rfc8737#section-6.1)
+pub const ACME_IDENTIFIER_OID: &str = "1.3.6.1.5.5.7.1.31";
+
+/// Computes the value of the `_acme-challenge` TXT record for a key authorization, i.e. its
+/// base64url-encoded SHA-256 digest
+///
+/// For more information, refer to [RFC 8555 § 8.4](https://datatracker.ietf.org/doc/html/rfc8555#section-8.4)
+#[cfg(feature = "crypto")]
+pub fn dns01_txt_record(key_authorization: &str) -> String {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use sha2::{Digest, Sha256};
+
+    URL_SAFE_NO_PAD.encode(Sha256::digest(key_authorization.as_bytes()))
+}
+
+/// Computes the SHA-256 digest of a key authorization, as carried in the `acmeIdentifier` extension
+///
+/// For more information, refer to [RFC 8737 § 3](https://datatracker.ietf.org/doc/html/rfc8737#section-3)
+#[cfg(feature = "crypto")]
+pub fn tls_alpn01_digest(key_authorization: &str) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(key_authorization.as_bytes()).into()
+}
+
+/// Computes the DER-encoded value (an OCTET STRING holding the key authorization digest) of the
+/// `acmeIdentifier` extension
+///
+/// For more information, refer to [RFC 8737 § 3](https://datatracker.ietf.org/doc/html/rfc8737#section-3)
+#[cfg(feature = "crypto")]
+pub fn tls_alpn01_extension_value(key_authorization: &str) -> Vec<u8> {
+    let mut value = vec![0x04, 0x20];
+    value.extend_from_slice(&tls_alpn01_digest(key_authorization));
+    value
 }
 
 /// Authorization resource status values
 ///
 /// For more information, refer to [RFC 8555 § 7.1.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.6)
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum AuthorizationStatus {
     #[cfg_attr(feature = "json", serde(rename = "pending"))]
@@ -157,3 +392,184 @@ pub enum AuthorizationStatus {
     #[cfg_attr(feature = "json", serde(rename = "invalid"))]
     Invalid,
 }
+
+impl AuthorizationStatus {
+    /// Returns the authorization status after the event, or an error if the RFC 8555 state
+    /// diagram does not allow the event in the current status
+    ///
+    /// For more information, refer to [RFC 8555 § 7.1.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.6)
+    pub fn transition(
+        self,
+        event: AuthorizationEvent,
+    ) -> Result<AuthorizationStatus, super::StatusTransitionError> {
+        use AuthorizationStatus::*;
+
+        match (self, event) {
+            (Pending, AuthorizationEvent::ChallengeValid) => Ok(Valid),
+            (Pending, AuthorizationEvent::Fail) => Ok(Invalid),
+            (Pending | Valid, AuthorizationEvent::Expire) => Ok(Expired),
+            (Pending | Valid, AuthorizationEvent::Deactivate) => Ok(Deactivated),
+            (Pending | Valid, AuthorizationEvent::Revoke) => Ok(Revoked),
+            (status, event) => Err(super::StatusTransitionError::Authorization(status, event)),
+        }
+    }
+
+    /// Returns true if the authorization has been validated (successfully or not), i.e. its status
+    /// is no longer `pending`
+    pub fn is_final(&self) -> bool {
+        !matches!(self, AuthorizationStatus::Pending)
+    }
+
+    /// Returns true if no event can change the authorization status
+    pub fn is_terminal(&self) -> bool {
+        !matches!(
+            self,
+            AuthorizationStatus::Pending | AuthorizationStatus::Valid
+        )
+    }
+
+    /// Returns the status value used in the authorization resource
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthorizationStatus::Pending => "pending",
+            AuthorizationStatus::Expired => "expired",
+            AuthorizationStatus::Deactivated => "deactivated",
+            AuthorizationStatus::Revoked => "revoked",
+            AuthorizationStatus::Valid => "valid",
+            AuthorizationStatus::Invalid => "invalid",
+        }
+    }
+}
+
+/// Authorization resource status transition events
+///
+/// For more information, refer to [RFC 8555 § 7.1.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.6)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AuthorizationEvent {
+    /// One of the authorization's challenges became `valid` (`pending` to `valid`)
+    ChallengeValid,
+    /// Challenge validation failed or an error occurred (`pending` to `invalid`)
+    Fail,
+    /// The authorization expired (to `expired`)
+    Expire,
+    /// The ACME client deactivated the authorization (to `deactivated`)
+    Deactivate,
+    /// The ACME provider revoked the authorization (to `revoked`)
+    Revoke,
+}
+
+impl AuthorizationEvent {
+    /// Returns a short description of the event
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthorizationEvent::ChallengeValid => "challenge valid",
+            AuthorizationEvent::Fail => "fail",
+            AuthorizationEvent::Expire => "expire",
+            AuthorizationEvent::Deactivate => "deactivate",
+            AuthorizationEvent::Revoke => "revoke",
+        }
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+
+    fn challenge(type_: &str) -> Challenge {
+        Challenge::from_str(&format!(
+            r#"{{"url":"https://example.com/acme/chall/1","type":"{}","status":"pending","token":"evaGxfADs6pSRb2LAv9IZf17Dt3juxGJ-PCt92wr-oA"}}"#,
+            type_
+        ))
+        .unwrap()
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn key_authorization() {
+        let key = crate::v2::jose::tests::rfc7638_key();
+        let key_authorization = "evaGxfADs6pSRb2LAv9IZf17Dt3juxGJ-PCt92wr-oA.NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs";
+        let challenge = challenge("http-01");
+
+        assert_eq!(
+            challenge.http01_path().unwrap(),
+            "/.well-known/acme-challenge/evaGxfADs6pSRb2LAv9IZf17Dt3juxGJ-PCt92wr-oA"
+        );
+        assert_eq!(
+            challenge.key_authorization(&key).unwrap(),
+            key_authorization
+        );
+        assert_eq!(
+            challenge.http01_response_body(&key).unwrap(),
+            key_authorization
+        );
+
+        let challenge = Challenge {
+            token: None,
+            ..challenge
+        };
+
+        assert_eq!(challenge.key_authorization(&key), None);
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn dns01_txt_record() {
+        let key = crate::v2::jose::tests::rfc7638_key();
+
+        assert_eq!(
+            challenge("dns-01").dns01_txt_record(&key).unwrap(),
+            "ZTRx1Ckl1-tM05o5zaizTTA0yUy5AGereMgSNWC6Ll8"
+        );
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn tls_alpn01_digest() {
+        let key = crate::v2::jose::tests::rfc7638_key();
+
+        assert_eq!(
+            challenge("tls-alpn-01").tls_alpn01_digest(&key).unwrap(),
+            [
+                0x65, 0x34, 0x71, 0xd4, 0x29, 0x25, 0xd7, 0xeb, 0x4c, 0xd3, 0x9a, 0x39, 0xcd, 0xa8,
+                0xb3, 0x4d, 0x30, 0x34, 0xc9, 0x4c, 0xb9, 0x00, 0x67, 0xab, 0x78, 0xc8, 0x12, 0x35,
+                0x60, 0xba, 0x2e, 0x5f,
+            ]
+        );
+    }
+
+    #[test]
+    fn challenge_types() {
+        for (name, type_) in [
+            ("http-01", ChallengeType::Http01),
+            ("dns-01", ChallengeType::Dns01),
+            ("tls-alpn-01", ChallengeType::TlsAlpn01),
+        ] {
+            let challenge = challenge(name);
+            assert_eq!(challenge.type_, type_);
+            assert_eq!(challenge.type_.as_str(), name);
+            assert!(challenge
+                .to_string()
+                .unwrap()
+                .contains(&format!(r#""type":"{}""#, name)));
+        }
+    }
+
+    #[test]
+    fn unknown_challenge_type() {
+        let challenge = challenge("dns-account-01");
+
+        assert_eq!(
+            challenge.type_,
+            ChallengeType::Unknown("dns-account-01".to_string())
+        );
+        assert_eq!(challenge.type_.as_str(), "dns-account-01");
+        assert_eq!(challenge.status, ChallengeStatus::Pending);
+
+        let json = challenge.to_string().unwrap();
+        assert!(json.contains(r#""type":"dns-account-01""#));
+        assert_eq!(
+            Challenge::from_str(&json).unwrap().type_,
+            ChallengeType::Unknown("dns-account-01".to_string())
+        );
+    }
+}
@@ -0,0 +1,1362 @@
+use std::time::Duration;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    AccountUrl, Authorization, AuthorizationStatus, AuthorizationUrl, CertificateUrl, Challenge,
+    ChallengeStatus, ChallengeType, ChallengeUrl, ContentType, Directory, ExternalAccountBinding,
+    HeaderError, Identifier, JsonWebSignature, JsonWebSignatureProtected, JwsError, Link,
+    LinkRelation, Location, NewAccount, NewOrder, NoncePool, Order, OrderFinalize, OrderStatus,
+    OrderUrl, ReplayNonce, RetryAfter, SigningKey,
+};
+
+/// HTTP request methods used by ACME clients
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HttpMethod {
+    Get,
+    Head,
+    Post,
+}
+
+impl HttpMethod {
+    /// Returns the method name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Head => "HEAD",
+            HttpMethod::Post => "POST",
+        }
+    }
+}
+
+/// Defines an HTTP request to be sent to the ACME provider by the caller of an [`AcmeClient`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpRequest {
+    /// Request method
+    pub method: HttpMethod,
+    /// Request URL
+    pub url: String,
+    /// Request header fields (name, value)
+    pub headers: Vec<(String, String)>,
+    /// Request body, empty for GET and HEAD requests
+    pub body: Vec<u8>,
+}
+
+/// Defines an HTTP response received from the ACME provider, to be passed to an [`AcmeClient`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HttpResponse {
+    /// Response status code
+    pub status: u16,
+    /// Response header fields (name, value)
+    pub headers: Vec<(String, String)>,
+    /// Response body
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Returns the value of the first header field with the given (case-insensitive) name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the values of all the header fields with the given (case-insensitive) name
+    pub fn header_values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.headers
+            .iter()
+            .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Defines the next action the caller of an [`AcmeClient`] must take
+#[derive(Clone, Debug)]
+pub enum ClientAction {
+    /// Send the request, then pass the response to `AcmeClient::handle_response`
+    Request(HttpRequest),
+    /// Provision the challenge response for the identifier (e.g. serve the key authorization for
+    /// http-01 or publish `dns01_txt_record(key_authorization)` for dns-01), then call
+    /// `AcmeClient::challenge_provisioned`
+    ProvisionChallenge {
+        identifier: Identifier,
+        challenge: Box<Challenge>,
+        key_authorization: String,
+    },
+    /// Wait for the duration, then ask for the next action
+    Wait(Duration),
+    /// The certificate has been issued
+    Done {
+        /// PEM certificate chain
+        chain: String,
+        /// URLs of the alternate certificate chains offered by the ACME provider, which can be
+        /// downloaded with POST-as-GET requests
+        ///
+        /// For more information, refer to [RFC 8555 § 7.4.2](https://datatracker.ietf.org/doc/html/rfc8555#section-7.4.2)
+        alternates: Vec<CertificateUrl>,
+    },
+}
+
+#[derive(Clone, Debug)]
+enum State {
+    Directory,
+    Account,
+    Order,
+    Authorization,
+    Provision(Identifier, Box<Challenge>),
+    Respond(ChallengeUrl),
+    PollAuthorization,
+    Finalize,
+    PollOrder,
+    Certificate(CertificateUrl),
+    Done(String, Vec<CertificateUrl>),
+}
+
+/// Defines a sans-IO ACME client, which issues a certificate by emitting HTTP requests and
+/// consuming their responses, without performing any I/O itself.
+///
+/// The client fetches the directory, registers (or looks up) the account, creates the order,
+/// has the caller provision a challenge for each pending authorization, polls the authorizations,
+/// finalizes the order, polls it and downloads the certificate. Nonces are collected in a
+/// [`NoncePool`], fetched as needed and a request failing with a `badNonce` error is retried once.
+///
+/// ```ignore
+/// loop {
+///     match client.next_action()? {
+///         ClientAction::Request(request) => client.handle_response(send(request))?,
+///         ClientAction::ProvisionChallenge { challenge, key_authorization, .. } => {
+///             provision(&challenge, &key_authorization);
+///             client.challenge_provisioned()?;
+///         }
+///         ClientAction::Wait(duration) => std::thread::sleep(duration),
+///         ClientAction::Done { chain, .. } => break chain,
+///     }
+/// }
+/// ```
+///
+/// For more information, refer to [RFC 8555 § 7](https://datatracker.ietf.org/doc/html/rfc8555#section-7)
+#[derive(Debug)]
+pub struct AcmeClient {
+    key: SigningKey,
+    directory_url: String,
+    new_account: NewAccount,
+    new_order: NewOrder,
+    certificate_signing_request: Vec<u8>,
+    challenge_types: Vec<ChallengeType>,
+    external_account_binding: Option<ExternalAccountBinding>,
+    poll_interval: Duration,
+    state: State,
+    directory: Option<Directory>,
+    nonces: NoncePool,
+    account: Option<AccountUrl>,
+    order: Option<(OrderUrl, Order)>,
+    authorization: usize,
+    finalized: bool,
+    wait: Option<Duration>,
+    awaiting_response: bool,
+    fetching_nonce: bool,
+}
+
+impl AcmeClient {
+    /// Creates a client which registers the account and issues a certificate for the order using
+    /// the DER-encoded CSR
+    pub fn new(
+        key: SigningKey,
+        directory_url: impl Into<String>,
+        new_account: NewAccount,
+        new_order: NewOrder,
+        certificate_signing_request: Vec<u8>,
+    ) -> AcmeClient {
+        AcmeClient {
+            key,
+            directory_url: directory_url.into(),
+            new_account,
+            new_order,
+            certificate_signing_request,
+            challenge_types: vec![
+                ChallengeType::Http01,
+                ChallengeType::Dns01,
+                ChallengeType::TlsAlpn01,
+            ],
+            external_account_binding: None,
+            poll_interval: Duration::from_secs(2),
+            state: State::Directory,
+            directory: None,
+            nonces: NoncePool::default(),
+            account: None,
+            order: None,
+            authorization: 0,
+            finalized: false,
+            wait: None,
+            awaiting_response: false,
+            fetching_nonce: false,
+        }
+    }
+
+    /// Uses an existing account instead of registering one
+    pub fn with_account(self, account: AccountUrl) -> AcmeClient {
+        AcmeClient {
+            account: Some(account),
+            ..self
+        }
+    }
+
+    /// Binds the new account to an external account
+    ///
+    /// For more information, refer to [RFC 8555 § 7.3.4](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3.4)
+    pub fn with_external_account_binding(self, binding: ExternalAccountBinding) -> AcmeClient {
+        AcmeClient {
+            external_account_binding: Some(binding),
+            ..self
+        }
+    }
+
+    /// Sets the challenge types the client may use, in order of preference (default: http-01,
+    /// dns-01, tls-alpn-01)
+    pub fn with_challenge_types(self, challenge_types: Vec<ChallengeType>) -> AcmeClient {
+        AcmeClient {
+            challenge_types,
+            ..self
+        }
+    }
+
+    /// Sets the interval between polls when the ACME provider does not send a `Retry-After`
+    /// header field (default: 2 seconds)
+    pub fn with_poll_interval(self, poll_interval: Duration) -> AcmeClient {
+        AcmeClient {
+            poll_interval,
+            ..self
+        }
+    }
+
+    /// Returns the directory, once fetched
+    pub fn directory(&self) -> Option<&Directory> {
+        self.directory.as_ref()
+    }
+
+    /// Returns the account URL, once registered
+    pub fn account(&self) -> Option<&AccountUrl> {
+        self.account.as_ref()
+    }
+
+    /// Returns the order URL and the last order object received, once created
+    pub fn order(&self) -> Option<(&OrderUrl, &Order)> {
+        self.order.as_ref().map(|(url, order)| (url, order))
+    }
+
+    /// Returns the next action to take
+    pub fn next_action(&mut self) -> Result<ClientAction, ClientError> {
+        if self.awaiting_response {
+            return Err(ClientError::State(
+                "a response to the last request is awaited",
+            ));
+        }
+
+        if let Some(duration) = self.wait.take() {
+            return Ok(ClientAction::Wait(duration));
+        }
+
+        let request = match self.state.clone() {
+            State::Directory => request(HttpMethod::Get, &self.directory_url, vec![], vec![]),
+            State::Provision(identifier, challenge) => {
+                let key_authorization = challenge
+                    .key_authorization(&self.key.public_key())
+                    .ok_or_else(|| ClientError::Response("challenge has no token".to_string()))?;
+
+                return Ok(ClientAction::ProvisionChallenge {
+                    identifier,
+                    challenge,
+                    key_authorization,
+                });
+            }
+            State::Done(chain, alternates) => return Ok(ClientAction::Done { chain, alternates }),
+            _ if self.nonces.is_empty() => {
+                self.fetching_nonce = true;
+                let url = self.require_directory()?.new_nonce.as_str().to_string();
+                request(HttpMethod::Head, &url, vec![], vec![])
+            }
+            State::Account => {
+                let url = self.require_directory()?.new_account.as_str().to_string();
+                let mut new_account = self.new_account.clone();
+                if let Some(binding) = &self.external_account_binding {
+                    new_account.external_account_binding =
+                        Some(binding.sign(&self.key.public_key(), &url)?);
+                }
+                self.post(&url, Some(&new_account), false)?
+            }
+            State::Order => {
+                let url = self.require_directory()?.new_order.as_str().to_string();
+                self.post(&url, Some(&self.new_order.clone()), true)?
+            }
+            State::Authorization | State::PollAuthorization => {
+                let url = self.authorization_url()?.as_str().to_string();
+                self.post(&url, None::<&()>, true)?
+            }
+            State::Respond(url) => self.post(url.as_str(), Some(&serde_json::json!({})), true)?,
+            State::Finalize => {
+                let url = self.current_order()?.1.finalize.as_str().to_string();
+                let finalize = OrderFinalize {
+                    certificate_signing_request: URL_SAFE_NO_PAD
+                        .encode(&self.certificate_signing_request),
+                };
+                self.post(&url, Some(&finalize), true)?
+            }
+            State::PollOrder => {
+                let url = self.current_order()?.0.as_str().to_string();
+                self.post(&url, None::<&()>, true)?
+            }
+            State::Certificate(url) => {
+                let mut request = self.post(url.as_str(), None::<&()>, true)?;
+                request.headers.push((
+                    "Accept".to_string(),
+                    ContentType::PemCertificateChain.to_string(),
+                ));
+                request
+            }
+        };
+
+        self.awaiting_response = true;
+
+        Ok(ClientAction::Request(request))
+    }
+
+    /// Consumes the response to the last request
+    ///
+    /// Error responses are returned as `ClientError::Acme`, except for the first `badNonce` error,
+    /// which is retried by the next request.
+    pub fn handle_response(&mut self, response: HttpResponse) -> Result<(), ClientError> {
+        if !self.awaiting_response {
+            return Err(ClientError::State("no request is awaiting a response"));
+        }
+
+        self.awaiting_response = false;
+
+        // A malformed nonce is discarded rather than failing an otherwise valid response
+        if let Some(Ok(nonce)) = response.header(ReplayNonce::NAME).map(ReplayNonce::parse) {
+            self.nonces.push(nonce.0);
+        }
+
+        if response.status >= 400 {
+            self.fetching_nonce = false;
+            let error = match super::Error::from_str(&String::from_utf8_lossy(&response.body)) {
+                Ok(error) => error,
+                Err(_) => return Err(ClientError::Status(response.status)),
+            };
+
+            if self.nonces.retry(&error) {
+                return Ok(());
+            }
+
+            return Err(ClientError::Acme(Box::new(error)));
+        }
+
+        if self.fetching_nonce {
+            self.fetching_nonce = false;
+            return match self.nonces.is_empty() {
+                false => Ok(()),
+                true => Err(missing_header(ReplayNonce::NAME)),
+            };
+        }
+
+        self.nonces.succeeded();
+
+        match self.state.clone() {
+            State::Directory => {
+                self.directory = Some(parse(&response)?);
+                self.state = match self.account {
+                    Some(_) => State::Order,
+                    None => State::Account,
+                };
+            }
+            State::Account => {
+                let request_url = self.require_directory()?.new_account.as_str();
+                self.account = Some(AccountUrl::new(location(&response, request_url)?));
+                self.state = State::Order;
+            }
+            State::Order => {
+                let request_url = self.require_directory()?.new_order.as_str();
+                let url = OrderUrl::new(location(&response, request_url)?);
+                let order: Order = parse(&response)?;
+                self.order = Some((url, order));
+                self.authorization = 0;
+                self.state = match self.current_order()?.1.authorizations.is_empty() {
+                    true => State::PollOrder,
+                    false => State::Authorization,
+                };
+                self.handle_order()?;
+            }
+            State::Authorization | State::PollAuthorization => {
+                let authorization: Authorization = parse(&response)?;
+                self.handle_authorization(authorization, &response)?;
+            }
+            State::Respond(_) => {
+                self.state = State::PollAuthorization;
+                self.wait = Some(self.retry_after(&response));
+            }
+            State::Finalize | State::PollOrder => {
+                if let State::Finalize = self.state {
+                    self.finalized = true;
+                    self.state = State::PollOrder;
+                }
+                let order: Order = parse(&response)?;
+                if let Some((_, current)) = &mut self.order {
+                    *current = order;
+                }
+                self.handle_order()?;
+                if let State::PollOrder = self.state {
+                    self.wait = Some(self.retry_after(&response));
+                }
+            }
+            State::Certificate(url) => {
+                let alternates = Link::parse_all(response.header_values(Link::NAME))?
+                    .into_iter()
+                    .filter(|link| link.relation == LinkRelation::Alternate)
+                    .map(|link| CertificateUrl::new(Location(link.url).resolve(url.as_str())))
+                    .collect();
+                let chain = String::from_utf8(response.body)
+                    .map_err(|_| ClientError::Response("certificate is not UTF-8".to_string()))?;
+                self.state = State::Done(chain, alternates);
+            }
+            State::Provision(..) | State::Done(..) => {
+                return Err(ClientError::State("no request is awaiting a response"))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Signals that the challenge returned by `ClientAction::ProvisionChallenge` has been
+    /// provisioned and the ACME provider can attempt to validate it
+    pub fn challenge_provisioned(&mut self) -> Result<(), ClientError> {
+        match &self.state {
+            State::Provision(_, challenge) => {
+                self.state = State::Respond(challenge.url.clone());
+                Ok(())
+            }
+            _ => Err(ClientError::State("no challenge is awaiting provisioning")),
+        }
+    }
+
+    fn handle_authorization(
+        &mut self,
+        authorization: Authorization,
+        response: &HttpResponse,
+    ) -> Result<(), ClientError> {
+        match authorization.status {
+            AuthorizationStatus::Valid => {
+                self.authorization += 1;
+                self.state = match self.authorization < self.current_order()?.1.authorizations.len()
+                {
+                    true => State::Authorization,
+                    false => State::PollOrder,
+                };
+            }
+            AuthorizationStatus::Pending => {
+                if let State::PollAuthorization = self.state {
+                    self.wait = Some(self.retry_after(response));
+                    return Ok(());
+                }
+
+                let challenge = self
+                    .challenge_types
+                    .iter()
+                    .filter(|type_| authorization.identifier.supports_challenge(type_))
+                    .find_map(|type_| {
+                        authorization
+                            .challenges
+                            .iter()
+                            .find(|challenge| &challenge.type_ == type_)
+                    })
+                    .cloned()
+                    .ok_or_else(|| {
+                        ClientError::NoSupportedChallenge(authorization.identifier.clone())
+                    })?;
+
+                self.state = match challenge.status {
+                    ChallengeStatus::Pending => {
+                        State::Provision(authorization.identifier, Box::new(challenge))
+                    }
+                    _ => {
+                        self.wait = Some(self.retry_after(response));
+                        State::PollAuthorization
+                    }
+                };
+            }
+            _ => {
+                let error = authorization
+                    .challenges
+                    .into_iter()
+                    .find_map(|challenge| challenge.error)
+                    .map(Box::new);
+
+                return Err(ClientError::AuthorizationFailed(
+                    self.authorization_url()?.clone(),
+                    error,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_order(&mut self) -> Result<(), ClientError> {
+        let (url, order) = self.current_order()?;
+
+        match order.status {
+            OrderStatus::Pending => (),
+            OrderStatus::Ready if !self.finalized => self.state = State::Finalize,
+            OrderStatus::Ready | OrderStatus::Processing => self.state = State::PollOrder,
+            OrderStatus::Valid => {
+                let certificate = order.certificate.clone().ok_or_else(|| {
+                    ClientError::Response("valid order has no certificate URL".to_string())
+                })?;
+                self.state = State::Certificate(certificate);
+            }
+            OrderStatus::Invalid => {
+                return Err(ClientError::OrderFailed(
+                    url.clone(),
+                    order.error.clone().map(Box::new),
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    fn post<T: Serialize>(
+        &mut self,
+        url: &str,
+        payload: Option<&T>,
+        key_id: bool,
+    ) -> Result<HttpRequest, ClientError> {
+        let protected = JsonWebSignatureProtected {
+            algorithm: self.key.algorithm(),
+            nonce: self.nonces.pop(),
+            url: url.to_string(),
+            json_web_key: match key_id {
+                true => None,
+                false => Some(self.key.public_key()),
+            },
+            key_id: match key_id {
+                true => Some(
+                    self.account
+                        .as_ref()
+                        .ok_or(ClientError::State("no account URL"))?
+                        .as_str()
+                        .to_string(),
+                ),
+                false => None,
+            },
+        };
+
+        let signature = match payload {
+            Some(payload) => JsonWebSignature::sign(&protected, payload, &self.key)?,
+            None => JsonWebSignature::sign_empty(&protected, &self.key)?,
+        };
+        let body = signature
+            .to_string()
+            .map_err(|e| JwsError::Json(e.to_string()))?;
+
+        Ok(request(
+            HttpMethod::Post,
+            url,
+            vec![(
+                ContentType::NAME.to_string(),
+                ContentType::JoseJson.to_string(),
+            )],
+            body.into_bytes(),
+        ))
+    }
+
+    fn require_directory(&self) -> Result<&Directory, ClientError> {
+        self.directory
+            .as_ref()
+            .ok_or(ClientError::State("no directory"))
+    }
+
+    fn current_order(&self) -> Result<&(OrderUrl, Order), ClientError> {
+        self.order.as_ref().ok_or(ClientError::State("no order"))
+    }
+
+    fn authorization_url(&self) -> Result<&AuthorizationUrl, ClientError> {
+        self.current_order()?
+            .1
+            .authorizations
+            .get(self.authorization)
+            .ok_or(ClientError::State("no authorization"))
+    }
+
+    fn retry_after(&self, response: &HttpResponse) -> Duration {
+        response
+            .header(RetryAfter::NAME)
+            .and_then(|value| RetryAfter::parse(value).ok())
+            .and_then(|retry_after| retry_after.delay())
+            .unwrap_or(self.poll_interval)
+    }
+}
+
+fn request(
+    method: HttpMethod,
+    url: &str,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+) -> HttpRequest {
+    HttpRequest {
+        method,
+        url: url.to_string(),
+        headers,
+        body,
+    }
+}
+
+fn parse<T: DeserializeOwned>(response: &HttpResponse) -> Result<T, ClientError> {
+    serde_json::from_slice(&response.body).map_err(|e| ClientError::Response(e.to_string()))
+}
+
+fn location(response: &HttpResponse, request_url: &str) -> Result<String, ClientError> {
+    let location = response
+        .header(Location::NAME)
+        .ok_or_else(|| missing_header(Location::NAME))?;
+
+    Ok(Location::parse(location)?.resolve(request_url))
+}
+
+fn missing_header(name: &str) -> ClientError {
+    ClientError::Response(format!("missing {} header field", name))
+}
+
+/// Errors encountered by an ACME client
+#[derive(Clone, Debug)]
+pub enum ClientError {
+    /// A request could not be signed
+    Jws(JwsError),
+    /// A response is invalid
+    Response(String),
+    /// The ACME provider returned an error
+    Acme(Box<super::Error>),
+    /// The ACME provider returned an unexpected status code without a problem document
+    Status(u16),
+    /// None of the allowed challenge types can be used to validate the identifier
+    NoSupportedChallenge(Identifier),
+    /// The authorization is no longer pending or valid, with the challenge error if any
+    AuthorizationFailed(AuthorizationUrl, Option<Box<super::Error>>),
+    /// The order is invalid, with the order error if any
+    OrderFailed(OrderUrl, Option<Box<super::Error>>),
+    /// The client was used out of order
+    State(&'static str),
+}
+
+impl From<JwsError> for ClientError {
+    fn from(error: JwsError) -> ClientError {
+        ClientError::Jws(error)
+    }
+}
+
+impl From<HeaderError> for ClientError {
+    fn from(error: HeaderError) -> ClientError {
+        ClientError::Response(error.to_string())
+    }
+}
+
+impl std::fmt::Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Jws(error) => write!(f, "request could not be signed: {}", error),
+            ClientError::Response(e) => write!(f, "response is invalid: {}", e),
+            ClientError::Acme(error) => write!(f, "ACME provider returned an error: {}", error),
+            ClientError::Status(status) => {
+                write!(f, "ACME provider returned status code {}", status)
+            }
+            ClientError::NoSupportedChallenge(identifier) => write!(
+                f,
+                "no supported challenge for identifier {}",
+                identifier.value
+            ),
+            ClientError::AuthorizationFailed(url, Some(error)) => {
+                write!(f, "authorization {} failed: {}", url, error)
+            }
+            ClientError::AuthorizationFailed(url, None) => {
+                write!(f, "authorization {} failed", url)
+            }
+            ClientError::OrderFailed(url, Some(error)) => {
+                write!(f, "order {} failed: {}", url, error)
+            }
+            ClientError::OrderFailed(url, None) => write!(f, "order {} failed", url),
+            ClientError::State(e) => write!(f, "client used out of order: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2::{JwsAlgorithm, SignedRequest};
+
+    const DIRECTORY: &str = r#"{
+        "newNonce": "https://ca.example/new-nonce",
+        "newAccount": "https://ca.example/new-account",
+        "newOrder": "https://ca.example/new-order",
+        "revokeCert": "https://ca.example/revoke-cert",
+        "keyChange": "https://ca.example/key-change"
+    }"#;
+    const ORDER: &str = r#"{
+        "status": "pending",
+        "identifiers": [{"type": "dns", "value": "example.com"}],
+        "authorizations": ["https://ca.example/authz/1"],
+        "finalize": "https://ca.example/order/1/finalize"
+    }"#;
+    const AUTHORIZATION: &str = r#"{
+        "status": "pending",
+        "identifier": {"type": "dns", "value": "example.com"},
+        "challenges": [
+            {"url": "https://ca.example/chall/1", "type": "tls-alpn-01", "status": "pending", "token": "dG9rZW4"},
+            {"url": "https://ca.example/chall/2", "type": "http-01", "status": "pending", "token": "dG9rZW4"}
+        ]
+    }"#;
+
+    const NONCE: &[(&str, &str)] = &[("Replay-Nonce", "bm9uY2U")];
+
+    fn acme_client() -> AcmeClient {
+        let pkcs8 = SigningKey::generate_pkcs8(&JwsAlgorithm::Es256).unwrap();
+        let key = SigningKey::from_pkcs8(JwsAlgorithm::Es256, &pkcs8).unwrap();
+        let new_order = NewOrder {
+            identifiers: vec![Identifier::dns("example.com").unwrap()],
+            not_before: None,
+            not_after: None,
+            replaces: None,
+        };
+
+        AcmeClient::new(
+            key,
+            "https://ca.example/directory",
+            NewAccount::from_str(r#"{"termsOfServiceAgreed": true}"#).unwrap(),
+            new_order,
+            b"csr".to_vec(),
+        )
+        .with_poll_interval(Duration::ZERO)
+    }
+
+    /// Expects the client to send a request to the URL, then answers it with the canned response,
+    /// adding a fresh nonce to responses to POST requests
+    fn exchange(
+        client: &mut AcmeClient,
+        method: HttpMethod,
+        url: &str,
+        response: (u16, &[(&str, &str)], &str),
+    ) -> Result<HttpRequest, ClientError> {
+        let request = match client.next_action()? {
+            ClientAction::Request(request) => request,
+            action => panic!("unexpected action: {:?}", action),
+        };
+        assert_eq!((request.method, request.url.as_str()), (method, url));
+
+        let (status, headers, body) = response;
+        let mut headers: Vec<(String, String)> = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        if method == HttpMethod::Post {
+            headers.push((ReplayNonce::NAME.to_string(), "bm9uY2U".to_string()));
+        }
+
+        client.handle_response(HttpResponse {
+            status,
+            headers,
+            body: body.as_bytes().to_vec(),
+        })?;
+
+        Ok(request)
+    }
+
+    /// Drives the client until it creates the order
+    fn create_order(client: &mut AcmeClient) {
+        let get = HttpMethod::Get;
+        let head = HttpMethod::Head;
+        let post = HttpMethod::Post;
+        let order_url = &[("Location", "https://ca.example/order/1")][..];
+
+        exchange(
+            client,
+            get,
+            "https://ca.example/directory",
+            (200, &[], DIRECTORY),
+        )
+        .unwrap();
+        exchange(
+            client,
+            head,
+            "https://ca.example/new-nonce",
+            (200, NONCE, ""),
+        )
+        .unwrap();
+
+        // Relative Location header fields are resolved against the request URL
+        let request = exchange(
+            client,
+            post,
+            "https://ca.example/new-account",
+            (201, &[("Location", "/acct/1")], "{}"),
+        )
+        .unwrap();
+        let request: SignedRequest<NewAccount> =
+            SignedRequest::from_str(std::str::from_utf8(&request.body).unwrap()).unwrap();
+        assert!(request.protected.json_web_key.is_some());
+        assert_eq!(request.protected.nonce.as_deref(), Some("bm9uY2U"));
+        assert_eq!(
+            client.account().map(AccountUrl::as_str),
+            Some("https://ca.example/acct/1")
+        );
+
+        let request = exchange(
+            client,
+            post,
+            "https://ca.example/new-order",
+            (201, order_url, ORDER),
+        )
+        .unwrap();
+        let request: SignedRequest<NewOrder> =
+            SignedRequest::from_str(std::str::from_utf8(&request.body).unwrap()).unwrap();
+        assert_eq!(
+            request.protected.key_id.as_deref(),
+            Some("https://ca.example/acct/1")
+        );
+    }
+
+    #[test]
+    fn canned_issuance() {
+        let post = HttpMethod::Post;
+        let mut client = acme_client();
+        create_order(&mut client);
+
+        exchange(
+            &mut client,
+            post,
+            "https://ca.example/authz/1",
+            (200, &[], AUTHORIZATION),
+        )
+        .unwrap();
+        match client.next_action().unwrap() {
+            ClientAction::ProvisionChallenge {
+                identifier,
+                challenge,
+                ..
+            } => {
+                assert_eq!(identifier.value, "example.com");
+                assert_eq!(challenge.type_, ChallengeType::Http01);
+            }
+            action => panic!("unexpected action: {:?}", action),
+        }
+        client.challenge_provisioned().unwrap();
+
+        let processing = r#"{
+            "url": "https://ca.example/chall/2",
+            "type": "http-01",
+            "status": "processing",
+            "token": "dG9rZW4"
+        }"#;
+        exchange(
+            &mut client,
+            post,
+            "https://ca.example/chall/2",
+            (200, &[("Retry-After", "3")], processing),
+        )
+        .unwrap();
+        assert!(matches!(
+            client.next_action(),
+            Ok(ClientAction::Wait(duration)) if duration == Duration::from_secs(3)
+        ));
+
+        let valid = AUTHORIZATION.replace(r#""status": "pending","#, r#""status": "valid","#);
+        exchange(
+            &mut client,
+            post,
+            "https://ca.example/authz/1",
+            (200, &[], &valid),
+        )
+        .unwrap();
+
+        let ready = ORDER.replace("pending", "ready");
+        exchange(
+            &mut client,
+            post,
+            "https://ca.example/order/1",
+            (200, &[], &ready),
+        )
+        .unwrap();
+
+        let valid = ORDER.replace(
+            r#""status": "pending","#,
+            r#""status": "valid", "certificate": "https://ca.example/cert/1","#,
+        );
+        exchange(
+            &mut client,
+            post,
+            "https://ca.example/order/1/finalize",
+            (200, &[], &valid),
+        )
+        .unwrap();
+
+        let links = [
+            ("Link", r#"<https://ca.example/cert/1/1>;rel="alternate""#),
+            (
+                "link",
+                r#"</cert/1/2>;rel="alternate", <https://ca.example/directory>;rel="index""#,
+            ),
+        ];
+        let request = exchange(
+            &mut client,
+            post,
+            "https://ca.example/cert/1",
+            (200, &links, include_str!("testdata/leaf.pem")),
+        )
+        .unwrap();
+        assert!(request.headers.contains(&(
+            "Accept".to_string(),
+            "application/pem-certificate-chain".to_string()
+        )));
+
+        match client.next_action().unwrap() {
+            ClientAction::Done { chain, alternates } => {
+                assert_eq!(chain, include_str!("testdata/leaf.pem"));
+                assert_eq!(
+                    alternates,
+                    vec![
+                        CertificateUrl::new("https://ca.example/cert/1/1"),
+                        CertificateUrl::new("https://ca.example/cert/1/2"),
+                    ]
+                );
+            }
+            action => panic!("unexpected action: {:?}", action),
+        }
+    }
+
+    #[test]
+    fn canned_errors() {
+        let get = HttpMethod::Get;
+        let head = HttpMethod::Head;
+        let post = HttpMethod::Post;
+
+        let mut client = acme_client();
+        assert!(matches!(
+            client.handle_response(HttpResponse::default()),
+            Err(ClientError::State(_))
+        ));
+        assert!(matches!(
+            exchange(
+                &mut client,
+                get,
+                "https://ca.example/directory",
+                (503, &[], "")
+            ),
+            Err(ClientError::Status(503))
+        ));
+
+        let mut client = acme_client();
+        exchange(
+            &mut client,
+            get,
+            "https://ca.example/directory",
+            (200, &[], DIRECTORY),
+        )
+        .unwrap();
+        assert!(matches!(
+            exchange(
+                &mut client,
+                head,
+                "https://ca.example/new-nonce",
+                (200, &[], "")
+            ),
+            Err(ClientError::Response(_))
+        ));
+
+        let mut client = acme_client();
+        exchange(
+            &mut client,
+            get,
+            "https://ca.example/directory",
+            (200, &[], DIRECTORY),
+        )
+        .unwrap();
+        exchange(
+            &mut client,
+            head,
+            "https://ca.example/new-nonce",
+            (200, NONCE, ""),
+        )
+        .unwrap();
+        let error = r#"{"type": "urn:ietf:params:acme:error:invalidContact", "detail": "no"}"#;
+        match exchange(
+            &mut client,
+            post,
+            "https://ca.example/new-account",
+            (400, &[], error),
+        ) {
+            Err(ClientError::Acme(error)) => {
+                assert_eq!(error.type_, crate::v2::ErrorType::InvalidContact)
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        let mut client = acme_client();
+        exchange(
+            &mut client,
+            get,
+            "https://ca.example/directory",
+            (200, &[], DIRECTORY),
+        )
+        .unwrap();
+        exchange(
+            &mut client,
+            head,
+            "https://ca.example/new-nonce",
+            (200, NONCE, ""),
+        )
+        .unwrap();
+        assert!(matches!(
+            exchange(
+                &mut client,
+                post,
+                "https://ca.example/new-account",
+                (201, &[], "{}")
+            ),
+            Err(ClientError::Response(_))
+        ));
+    }
+}
+
+#[cfg(all(test, feature = "time"))]
+mod provider_tests {
+    use super::*;
+    use crate::v2::{
+        AcmeProvider, Error, ErrorType, JwsAlgorithm, MemoryStore, ProviderResource, SignedRequest,
+    };
+
+    const BASE_URL: &str = "https://acme.example.com/acme";
+
+    /// Answers client requests with an `AcmeProvider`, validating challenges and issuing (or
+    /// failing) certificates as soon as the client responds to or finalizes them
+    struct Server {
+        provider: AcmeProvider<MemoryStore>,
+        validation: Result<(), Error>,
+        issuance: Result<String, Error>,
+        retry_after: Option<&'static str>,
+        stale_nonces: usize,
+    }
+
+    impl Server {
+        fn new() -> Server {
+            Server {
+                provider: AcmeProvider::new(BASE_URL, MemoryStore::new()),
+                validation: Ok(()),
+                issuance: Ok(include_str!("testdata/leaf.pem").to_string()),
+                retry_after: None,
+                stale_nonces: 0,
+            }
+        }
+
+        fn handle(&mut self, request: &HttpRequest) -> HttpResponse {
+            let mut headers = vec![];
+            if request.method != HttpMethod::Get {
+                let nonce = match self.stale_nonces {
+                    0 => self.provider.new_nonce().unwrap(),
+                    _ => {
+                        self.stale_nonces -= 1;
+                        "c3RhbGUtbm9uY2U".to_string()
+                    }
+                };
+                headers.push((ReplayNonce::NAME.to_string(), nonce));
+            }
+
+            match self.serve(request, &mut headers) {
+                Ok((status, body)) => HttpResponse {
+                    status,
+                    headers,
+                    body,
+                },
+                Err(error) => HttpResponse {
+                    status: error.status.unwrap_or(400),
+                    headers,
+                    body: json(&*error),
+                },
+            }
+        }
+
+        fn serve(
+            &self,
+            request: &HttpRequest,
+            headers: &mut Vec<(String, String)>,
+        ) -> Result<(u16, Vec<u8>), Box<Error>> {
+            let body = std::str::from_utf8(&request.body).unwrap();
+            let provider = &self.provider;
+
+            if let Some(retry_after) = self.retry_after {
+                headers.push((RetryAfter::NAME.to_string(), retry_after.to_string()));
+            }
+
+            match provider.resource(&request.url).unwrap() {
+                ProviderResource::Directory => Ok((200, json(&provider.directory()?))),
+                ProviderResource::NewNonce => Ok((200, vec![])),
+                ProviderResource::NewAccount => {
+                    let (url, account, created) = provider.new_account(&signed(body)?)?;
+                    headers.push((Location::NAME.to_string(), url.as_str().to_string()));
+                    Ok((if created { 201 } else { 200 }, json(&account)))
+                }
+                ProviderResource::NewOrder => {
+                    let (url, order) = provider.new_order(&signed(body)?)?;
+                    headers.push((Location::NAME.to_string(), url.as_str().to_string()));
+                    Ok((201, json(&order)))
+                }
+                ProviderResource::Order(id) => {
+                    Ok((200, json(&provider.order(&id, &signed(body)?)?)))
+                }
+                ProviderResource::Authorization(id) => {
+                    Ok((200, json(&provider.authorization(&id, &signed(body)?)?)))
+                }
+                ProviderResource::Challenge(id) => {
+                    let challenge = provider.challenge(&id, &signed(body)?)?;
+                    if challenge.status == ChallengeStatus::Processing {
+                        provider.complete_challenge(&id, self.validation.clone())?;
+                    }
+                    Ok((200, json(&challenge)))
+                }
+                ProviderResource::Finalize(id) => {
+                    let order = provider.finalize(&id, &signed(body)?)?;
+                    match self.issuance.clone() {
+                        Ok(chain) => provider.issue_certificate(&id, chain)?,
+                        Err(error) => provider.fail_order(&id, error)?,
+                    };
+                    Ok((200, json(&order)))
+                }
+                ProviderResource::Certificate(id) => {
+                    Ok((200, provider.certificate(&id, &signed(body)?)?.into_bytes()))
+                }
+                resource => panic!("unexpected resource: {:?}", resource),
+            }
+        }
+    }
+
+    fn json<T: Serialize + ?Sized>(value: &T) -> Vec<u8> {
+        serde_json::to_vec(value).unwrap()
+    }
+
+    fn signed<T: DeserializeOwned>(body: &str) -> Result<SignedRequest<T>, Box<Error>> {
+        SignedRequest::from_str(body).map_err(|e| Box::new(e.into()))
+    }
+
+    fn acme_client(server: &Server, identifiers: &[&str]) -> AcmeClient {
+        let pkcs8 = SigningKey::generate_pkcs8(&JwsAlgorithm::Es256).unwrap();
+        let key = SigningKey::from_pkcs8(JwsAlgorithm::Es256, &pkcs8).unwrap();
+        let new_account = NewAccount::from_str(
+            r#"{"contact": ["mailto:admin@example.com"], "termsOfServiceAgreed": true}"#,
+        )
+        .unwrap();
+        let new_order = NewOrder {
+            identifiers: identifiers
+                .iter()
+                .map(|value| Identifier::dns(value).unwrap())
+                .collect(),
+            not_before: None,
+            not_after: None,
+            replaces: None,
+        };
+
+        // The provider stores the CSR without checking it
+        AcmeClient::new(
+            key,
+            server.provider.url(&ProviderResource::Directory),
+            new_account,
+            new_order,
+            b"csr".to_vec(),
+        )
+        .with_poll_interval(Duration::ZERO)
+    }
+
+    #[derive(Debug, Default)]
+    struct Log {
+        provisioned: Vec<(String, ChallengeType)>,
+        waits: Vec<Duration>,
+        requests: usize,
+    }
+
+    fn run(
+        client: &mut AcmeClient,
+        server: &mut Server,
+        log: &mut Log,
+    ) -> Result<String, ClientError> {
+        loop {
+            match client.next_action()? {
+                ClientAction::Request(request) => {
+                    log.requests += 1;
+                    assert!(log.requests < 100, "the client does not terminate");
+                    client.handle_response(server.handle(&request))?;
+                }
+                ClientAction::ProvisionChallenge {
+                    identifier,
+                    challenge,
+                    key_authorization,
+                } => {
+                    assert_eq!(
+                        key_authorization,
+                        challenge
+                            .key_authorization(&client.key.public_key())
+                            .unwrap()
+                    );
+                    log.provisioned.push((identifier.value, challenge.type_));
+                    client.challenge_provisioned()?;
+                }
+                ClientAction::Wait(duration) => log.waits.push(duration),
+                ClientAction::Done { chain, .. } => return Ok(chain),
+            }
+        }
+    }
+
+    #[test]
+    fn issuance() {
+        let mut server = Server::new();
+        let mut client = acme_client(&server, &["example.com", "*.example.com"]);
+        let mut log = Log::default();
+
+        let certificate = run(&mut client, &mut server, &mut log).unwrap();
+
+        assert_eq!(certificate, include_str!("testdata/leaf.pem"));
+        assert_eq!(
+            log.provisioned,
+            vec![
+                ("example.com".to_string(), ChallengeType::Http01),
+                ("example.com".to_string(), ChallengeType::Dns01),
+            ]
+        );
+        assert!(log.waits.iter().all(|wait| wait.is_zero()));
+        assert!(client.account().is_some());
+        assert_eq!(client.order().unwrap().1.status, OrderStatus::Valid);
+        assert!(matches!(
+            client.next_action(),
+            Ok(ClientAction::Done { chain, alternates }) if chain == certificate && alternates.is_empty()
+        ));
+        assert!(matches!(
+            client.handle_response(HttpResponse::default()),
+            Err(ClientError::State(_))
+        ));
+    }
+
+    #[test]
+    fn bad_nonce_retry() {
+        let mut server = Server::new();
+        server.stale_nonces = 1;
+        let mut client = acme_client(&server, &["example.com"]);
+        let mut log = Log::default();
+
+        assert!(run(&mut client, &mut server, &mut log).is_ok());
+
+        // A second consecutive badNonce error is returned
+        let mut server = Server::new();
+        server.stale_nonces = 2;
+        let mut client = acme_client(&server, &["example.com"]);
+
+        match run(&mut client, &mut server, &mut Log::default()) {
+            Err(ClientError::Acme(error)) => assert_eq!(error.type_, ErrorType::BadNonce),
+            result => panic!("unexpected result: {:?}", result),
+        }
+
+        // A malformed nonce is discarded
+        let mut server = Server::new();
+        let mut client = acme_client(&server, &["example.com"]);
+        match client.next_action().unwrap() {
+            ClientAction::Request(request) => {
+                let mut response = server.handle(&request);
+                response
+                    .headers
+                    .push((ReplayNonce::NAME.to_string(), "not a nonce".to_string()));
+                client.handle_response(response).unwrap();
+            }
+            action => panic!("unexpected action: {:?}", action),
+        }
+        assert!(matches!(
+            client.next_action(),
+            Ok(ClientAction::Request(HttpRequest {
+                method: HttpMethod::Head,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn invalid_authorization() {
+        let mut server = Server::new();
+        server.validation = Err(Error::new(
+            ErrorType::Unauthorized,
+            "the key authorization does not match",
+        ));
+        let mut client = acme_client(&server, &["example.com"]);
+
+        match run(&mut client, &mut server, &mut Log::default()) {
+            Err(ClientError::AuthorizationFailed(url, Some(error))) => {
+                assert_eq!(&url, &client.order().unwrap().1.authorizations[0]);
+                assert_eq!(error.type_, ErrorType::Unauthorized);
+                assert_eq!(
+                    error.detail.as_deref(),
+                    Some("the key authorization does not match")
+                );
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn invalid_order() {
+        let mut server = Server::new();
+        server.issuance = Err(Error::new(
+            ErrorType::RejectedIdentifier,
+            "example.com is not allowed",
+        ));
+        let mut client = acme_client(&server, &["example.com"]);
+
+        match run(&mut client, &mut server, &mut Log::default()) {
+            Err(ClientError::OrderFailed(url, Some(error))) => {
+                assert_eq!(&url, client.order().unwrap().0);
+                assert_eq!(client.order().unwrap().1.status, OrderStatus::Invalid);
+                assert_eq!(error.type_, ErrorType::RejectedIdentifier);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn retry_after() {
+        let mut server = Server::new();
+        server.retry_after = Some("7");
+        let mut client = acme_client(&server, &["example.com"]);
+        let mut log = Log::default();
+
+        run(&mut client, &mut server, &mut log).unwrap();
+
+        // After responding to the challenge and after finalizing the order
+        assert_eq!(log.waits, vec![Duration::from_secs(7); 2]);
+
+        // Dates in the past result in a zero delay
+        let mut server = Server::new();
+        server.retry_after = Some("Sun, 06 Nov 1994 08:49:37 GMT");
+        let mut client =
+            acme_client(&server, &["example.com"]).with_poll_interval(Duration::from_secs(5));
+        let mut log = Log::default();
+
+        run(&mut client, &mut server, &mut log).unwrap();
+
+        assert_eq!(log.waits, vec![Duration::ZERO; 2]);
+
+        // Invalid values fall back to the poll interval
+        let mut server = Server::new();
+        server.retry_after = Some("soon");
+        let mut client =
+            acme_client(&server, &["example.com"]).with_poll_interval(Duration::from_secs(5));
+        let mut log = Log::default();
+
+        run(&mut client, &mut server, &mut log).unwrap();
+
+        assert_eq!(log.waits, vec![Duration::from_secs(5); 2]);
+    }
+}
@@ -0,0 +1,267 @@
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// Defines an ACME account contact URI.
+///
+/// Only `mailto:` contacts are defined by RFC 8555; `tel:` contacts are parsed so that they can be
+/// reported as unsupported by the ACME provider, and any other scheme is rejected by `parse`.
+///
+/// Contacts are deserialized leniently: a contact which fails validation is kept as
+/// [`Contact::Other`], so that ACME providers can report it as an `invalidContact` or
+/// `unsupportedContact` problem using `check_supported`.
+///
+/// For more information, refer to [RFC 8555 § 7.3](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json", serde(from = "String", into = "String"))]
+pub enum Contact {
+    /// `mailto:` contact, holding the normalized email address
+    Email(String),
+    /// `tel:` contact, holding the telephone number
+    Telephone(String),
+    /// Contact which failed validation, holding the URI as received
+    Other(String),
+}
+
+impl Contact {
+    /// Parses and normalizes a contact URI
+    ///
+    /// The URI scheme is matched case-insensitively and the domain of an email address is
+    /// lowercased. `mailto:` URIs with header fields or more than one address are rejected.
+    pub fn parse(s: &str) -> Result<Contact, ContactError> {
+        let s = s.trim();
+        let (scheme, value) = s
+            .split_once(':')
+            .ok_or_else(|| ContactError::Malformed(s.to_string()))?;
+
+        match scheme.to_ascii_lowercase().as_str() {
+            "mailto" => Contact::parse_email(value).map(Contact::Email),
+            "tel" => match value.is_empty() {
+                true => Err(ContactError::Malformed(s.to_string())),
+                false => Ok(Contact::Telephone(value.to_string())),
+            },
+            _ => Err(ContactError::UnsupportedScheme(scheme.to_string())),
+        }
+    }
+
+    fn parse_email(value: &str) -> Result<String, ContactError> {
+        if value.contains('?') {
+            return Err(ContactError::HeaderFields(value.to_string()));
+        }
+
+        if value.contains(',') {
+            return Err(ContactError::MultipleAddresses(value.to_string()));
+        }
+
+        match value.rsplit_once('@') {
+            Some((local, domain))
+                if !local.is_empty()
+                    && !domain.is_empty()
+                    && !domain.contains('@')
+                    && !value.chars().any(|c| c.is_whitespace() || c.is_control()) =>
+            {
+                Ok(format!("{}@{}", local, domain.to_ascii_lowercase()))
+            }
+            _ => Err(ContactError::InvalidAddress(value.to_string())),
+        }
+    }
+
+    /// Returns true if the contact's scheme is supported by RFC 8555 (i.e. `mailto:`)
+    pub fn is_supported(&self) -> bool {
+        matches!(self, Contact::Email(_))
+    }
+
+    /// Returns an `unsupportedContact` error if the contact's scheme is not supported by RFC 8555,
+    /// or the validation error of a contact which failed validation
+    pub fn check_supported(&self) -> Result<(), ContactError> {
+        match self {
+            Contact::Email(_) => Ok(()),
+            Contact::Telephone(_) => Err(ContactError::UnsupportedScheme("tel".to_string())),
+            Contact::Other(uri) => Contact::parse(uri)?.check_supported(),
+        }
+    }
+
+    /// Returns the contact URI
+    pub fn to_uri(&self) -> String {
+        match self {
+            Contact::Email(address) => format!("mailto:{}", address),
+            Contact::Telephone(number) => format!("tel:{}", number),
+            Contact::Other(uri) => uri.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for Contact {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_uri())
+    }
+}
+
+impl std::str::FromStr for Contact {
+    type Err = ContactError;
+
+    fn from_str(s: &str) -> Result<Contact, ContactError> {
+        Contact::parse(s)
+    }
+}
+
+impl From<String> for Contact {
+    /// Parses a contact URI, keeping it as [`Contact::Other`] if it fails validation
+    fn from(s: String) -> Contact {
+        Contact::parse(&s).unwrap_or(Contact::Other(s))
+    }
+}
+
+impl From<Contact> for String {
+    fn from(contact: Contact) -> String {
+        contact.to_uri()
+    }
+}
+
+/// Errors encountered while validating a contact URI
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContactError {
+    /// The contact is not a URI
+    Malformed(String),
+    /// The contact URI scheme is not supported
+    UnsupportedScheme(String),
+    /// The `mailto:` URI contains header fields
+    HeaderFields(String),
+    /// The `mailto:` URI contains more than one address
+    MultipleAddresses(String),
+    /// The `mailto:` URI does not contain a valid address
+    InvalidAddress(String),
+}
+
+impl ContactError {
+    /// Returns the ACME error type an ACME provider should return for this error
+    pub fn error_type(&self) -> super::ErrorType {
+        match self {
+            ContactError::UnsupportedScheme(_) => super::ErrorType::UnsupportedContact,
+            _ => super::ErrorType::InvalidContact,
+        }
+    }
+}
+
+impl std::fmt::Display for ContactError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContactError::Malformed(s) => write!(f, "contact is not a valid URI: {}", s),
+            ContactError::UnsupportedScheme(s) => {
+                write!(f, "contact URI scheme is not supported: {}", s)
+            }
+            ContactError::HeaderFields(s) => {
+                write!(f, "mailto contact must not contain header fields: {}", s)
+            }
+            ContactError::MultipleAddresses(s) => {
+                write!(f, "mailto contact must contain a single address: {}", s)
+            }
+            ContactError::InvalidAddress(s) => {
+                write!(f, "mailto contact address is invalid: {}", s)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ContactError {}
+
+impl From<ContactError> for super::Error {
+    fn from(error: ContactError) -> super::Error {
+        super::Error::new(error.error_type(), error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2::ErrorType;
+
+    #[test]
+    fn parse_email() {
+        assert_eq!(
+            Contact::parse("MAILTO:Admin@Example.COM").unwrap(),
+            Contact::Email("Admin@example.com".to_string())
+        );
+        assert_eq!(
+            Contact::parse("mailto:admin@example.com").unwrap().to_uri(),
+            "mailto:admin@example.com"
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        for (uri, error_type) in [
+            ("admin@example.com", ErrorType::InvalidContact),
+            ("mailto:admin", ErrorType::InvalidContact),
+            (
+                "mailto:admin@example.com?subject=hi",
+                ErrorType::InvalidContact,
+            ),
+            (
+                "mailto:a@example.com,b@example.com",
+                ErrorType::InvalidContact,
+            ),
+            ("https://example.com/contact", ErrorType::UnsupportedContact),
+        ] {
+            assert_eq!(Contact::parse(uri).unwrap_err().error_type(), error_type);
+        }
+    }
+
+    #[test]
+    fn check_supported() {
+        assert!(Contact::parse("mailto:admin@example.com")
+            .unwrap()
+            .check_supported()
+            .is_ok());
+        assert_eq!(
+            Contact::parse("tel:+12025550123")
+                .unwrap()
+                .check_supported()
+                .unwrap_err()
+                .error_type(),
+            ErrorType::UnsupportedContact
+        );
+    }
+
+    #[test]
+    fn lenient_conversion() {
+        for (uri, error_type) in [
+            ("https://example.com/contact", ErrorType::UnsupportedContact),
+            ("mailto:admin", ErrorType::InvalidContact),
+        ] {
+            let contact = Contact::from(uri.to_string());
+
+            assert_eq!(contact, Contact::Other(uri.to_string()));
+            assert_eq!(contact.to_uri(), uri);
+            assert!(!contact.is_supported());
+            assert_eq!(
+                contact.check_supported().unwrap_err().error_type(),
+                error_type
+            );
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn lenient_deserialization() {
+        let account = crate::v2::NewAccount::from_str(
+            r#"{"contact":["mailto:admin@Example.com","https://example.com","mailto:admin"]}"#,
+        )
+        .unwrap();
+        let contact = account.contact.unwrap();
+
+        assert_eq!(contact[0], Contact::Email("admin@example.com".to_string()));
+        assert_eq!(
+            contact[1].check_supported().unwrap_err().error_type(),
+            ErrorType::UnsupportedContact
+        );
+        assert_eq!(
+            contact[2].check_supported().unwrap_err().error_type(),
+            ErrorType::InvalidContact
+        );
+        assert_eq!(
+            serde_json::to_string(&contact).unwrap(),
+            r#"["mailto:admin@example.com","https://example.com","mailto:admin"]"#
+        );
+    }
+}
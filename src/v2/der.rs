@@ -0,0 +1,199 @@
+//! Minimal DER reader used to extract fields from X.509 certificates
+
+pub(crate) const TAG_INTEGER: u8 = 0x02;
+#[cfg(all(feature = "crypto", feature = "json", feature = "time"))]
+pub(crate) const TAG_BIT_STRING: u8 = 0x03;
+pub(crate) const TAG_OCTET_STRING: u8 = 0x04;
+pub(crate) const TAG_OID: u8 = 0x06;
+pub(crate) const TAG_SEQUENCE: u8 = 0x30;
+pub(crate) const TAG_SET: u8 = 0x31;
+
+/// Reads a single TLV, returning its tag, contents and the remaining input
+pub(crate) fn read(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, input) = input.split_first()?;
+    let (&first, input) = input.split_first()?;
+
+    let (len, input) = match first {
+        0x00..=0x7f => (first as usize, input),
+        0x81..=0x84 => {
+            let n = (first & 0x7f) as usize;
+            if input.len() < n || input[0] == 0 {
+                return None;
+            }
+            let len = input[..n]
+                .iter()
+                .fold(0usize, |len, &b| (len << 8) | b as usize);
+            // DER requires the shortest length encoding
+            if len < 0x80 {
+                return None;
+            }
+            (len, &input[n..])
+        }
+        _ => return None,
+    };
+
+    if input.len() < len {
+        return None;
+    }
+
+    Some((tag, &input[..len], &input[len..]))
+}
+
+/// Reads a single TLV with the expected tag, returning its contents and the remaining input
+pub(crate) fn expect(input: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+    match read(input)? {
+        (t, contents, rest) if t == tag => Some((contents, rest)),
+        _ => None,
+    }
+}
+
+/// Returns the `TBSCertificate` contents of a DER-encoded X.509 certificate
+pub(crate) fn tbs_certificate(certificate: &[u8]) -> Option<&[u8]> {
+    let (certificate, _) = expect(certificate, TAG_SEQUENCE)?;
+    let (tbs, _) = expect(certificate, TAG_SEQUENCE)?;
+    Some(tbs)
+}
+
+/// Returns the serial number (the contents of the DER INTEGER) of a DER-encoded X.509 certificate
+pub(crate) fn serial_number(certificate: &[u8]) -> Option<&[u8]> {
+    let tbs = tbs_certificate(certificate)?;
+    let tbs = match read(tbs)? {
+        (0xa0, _, rest) => rest,
+        _ => tbs,
+    };
+    let (serial, _) = expect(tbs, TAG_INTEGER)?;
+    Some(serial)
+}
+
+/// Returns the issuer and subject `Name` contents of a DER-encoded X.509 certificate
+pub(crate) fn issuer_and_subject(certificate: &[u8]) -> Option<(&[u8], &[u8])> {
+    let tbs = tbs_certificate(certificate)?;
+    let tbs = match read(tbs)? {
+        (0xa0, _, rest) => rest,
+        _ => tbs,
+    };
+    let (_, tbs) = expect(tbs, TAG_INTEGER)?;
+    let (_, tbs) = expect(tbs, TAG_SEQUENCE)?;
+    let (issuer, tbs) = expect(tbs, TAG_SEQUENCE)?;
+    let (_, tbs) = expect(tbs, TAG_SEQUENCE)?;
+    let (subject, _) = expect(tbs, TAG_SEQUENCE)?;
+    Some((issuer, subject))
+}
+
+/// Returns the `subjectPublicKey` (the contents of the DER BIT STRING, without its unused bits
+/// count) of a DER-encoded X.509 certificate
+#[cfg(all(feature = "crypto", feature = "json", feature = "time"))]
+pub(crate) fn subject_public_key(certificate: &[u8]) -> Option<&[u8]> {
+    let tbs = tbs_certificate(certificate)?;
+    let tbs = match read(tbs)? {
+        (0xa0, _, rest) => rest,
+        _ => tbs,
+    };
+    let (_, tbs) = expect(tbs, TAG_INTEGER)?;
+    let (_, tbs) = expect(tbs, TAG_SEQUENCE)?;
+    let (_, tbs) = expect(tbs, TAG_SEQUENCE)?;
+    let (_, tbs) = expect(tbs, TAG_SEQUENCE)?;
+    let (_, tbs) = expect(tbs, TAG_SEQUENCE)?;
+    let (spki, _) = expect(tbs, TAG_SEQUENCE)?;
+    let (_, spki) = expect(spki, TAG_SEQUENCE)?;
+    let (key, _) = expect(spki, TAG_BIT_STRING)?;
+    match key.split_first()? {
+        (0, key) => Some(key),
+        _ => None,
+    }
+}
+
+/// Returns the first common name (`2.5.4.3`) of a `Name`'s contents
+pub(crate) fn common_name(name: &[u8]) -> Option<String> {
+    let mut names = name;
+    while !names.is_empty() {
+        let (mut attributes, rest) = expect(names, TAG_SET)?;
+        while !attributes.is_empty() {
+            let (attribute, rest) = expect(attributes, TAG_SEQUENCE)?;
+            let (id, value) = expect(attribute, TAG_OID)?;
+            if id == [0x55, 0x04, 0x03] {
+                return match read(value)? {
+                    // UTF8String, PrintableString, IA5String
+                    (0x0c | 0x13 | 0x16, value, _) => String::from_utf8(value.to_vec()).ok(),
+                    // BMPString
+                    (0x1e, value, _) => String::from_utf16(
+                        &value
+                            .chunks(2)
+                            .map(|c| Some(u16::from_be_bytes([c[0], *c.get(1)?])))
+                            .collect::<Option<Vec<_>>>()?,
+                    )
+                    .ok(),
+                    _ => None,
+                };
+            }
+            attributes = rest;
+        }
+        names = rest;
+    }
+
+    None
+}
+
+/// Returns the value (the contents of `extnValue`) of an extension of a DER-encoded X.509 certificate
+pub(crate) fn extension<'a>(certificate: &'a [u8], oid: &[u8]) -> Option<&'a [u8]> {
+    let tbs = tbs_certificate(certificate)?;
+    let mut fields = tbs;
+    let mut extensions = None;
+    while !fields.is_empty() {
+        let (tag, contents, rest) = read(fields)?;
+        if tag == 0xa3 {
+            extensions = Some(expect(contents, TAG_SEQUENCE)?.0);
+        }
+        fields = rest;
+    }
+
+    let mut extensions = extensions?;
+    while !extensions.is_empty() {
+        let (extension, rest) = expect(extensions, TAG_SEQUENCE)?;
+        let (id, mut extension) = expect(extension, TAG_OID)?;
+        if id == oid {
+            if let Some((0x01, _, rest)) = read(extension) {
+                extension = rest;
+            }
+            return Some(expect(extension, TAG_OCTET_STRING)?.0);
+        }
+        extensions = rest;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths() {
+        assert_eq!(
+            read(&[0x04, 0x01, 0xaa, 0xbb]),
+            Some((0x04, &[0xaa][..], &[0xbb][..]))
+        );
+
+        let mut long = vec![0x04, 0x81, 0x80];
+        long.extend([0; 0x80]);
+        assert_eq!(read(&long), Some((0x04, &[0; 0x80][..], &[][..])));
+
+        let mut longer = vec![0x04, 0x82, 0x01, 0x00];
+        longer.extend([0; 0x100]);
+        assert_eq!(
+            read(&longer).map(|(_, contents, _)| contents.len()),
+            Some(0x100)
+        );
+
+        // Non-minimal long-form lengths
+        assert_eq!(read(&[0x04, 0x81, 0x01, 0xaa]), None);
+        assert_eq!(read(&[0x04, 0x82, 0x00, 0x01, 0xaa]), None);
+        // Indefinite and overlong lengths
+        assert_eq!(read(&[0x04, 0x80, 0xaa, 0x00, 0x00]), None);
+        assert_eq!(read(&[0x04, 0x85, 0x01, 0x00, 0x00, 0x00, 0x00]), None);
+        // Truncated input
+        assert_eq!(read(&[0x04, 0x02, 0xaa]), None);
+        assert_eq!(read(&[0x04, 0x82, 0x01]), None);
+        assert_eq!(read(&[0x04]), None);
+    }
+}
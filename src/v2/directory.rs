@@ -9,23 +9,29 @@ use serde::{Deserialize, Serialize};
 pub struct Directory {
     /// New nonce URL
     #[cfg_attr(feature = "json", serde(rename = "newNonce"))]
-    pub new_nonce: String,
+    pub new_nonce: super::NewNonceUrl,
     /// New account URL
     #[cfg_attr(feature = "json", serde(rename = "newAccount"))]
-    pub new_account: String,
+    pub new_account: super::NewAccountUrl,
     /// New order URL
     #[cfg_attr(feature = "json", serde(rename = "newOrder"))]
-    pub new_order: String,
+    pub new_order: super::NewOrderUrl,
     /// New authorization URL
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
     #[cfg_attr(feature = "json", serde(rename = "newAuthz"))]
-    pub new_authorization: Option<String>,
+    pub new_authorization: Option<super::NewAuthorizationUrl>,
     /// Revoke certificate URL
     #[cfg_attr(feature = "json", serde(rename = "revokeCert"))]
-    pub revoke_certificate: String,
+    pub revoke_certificate: super::RevokeCertificateUrl,
     /// Key change URL
     #[cfg_attr(feature = "json", serde(rename = "keyChange"))]
-    pub key_change: String,
+    pub key_change: super::KeyChangeUrl,
+    /// Renewal information URL
+    ///
+    /// For more information, refer to [RFC 9773 § 4](https://datatracker.ietf.org/doc/html/rfc9773#section-4)
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(feature = "json", serde(rename = "renewalInfo"))]
+    pub renewal_info: Option<super::RenewalInfoUrl>,
     /// Metadata object
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
     #[cfg_attr(feature = "json", serde(rename = "meta"))]
@@ -13,11 +13,49 @@ pub struct Error {
     #[cfg_attr(feature = "json", serde(deserialize_with = "error_type_deserialize"))]
     pub type_: ErrorType,
     /// Error description
-    pub detail: String,
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(feature = "json", serde(default))]
+    pub detail: Option<String>,
+    /// Short, human-readable summary of the error type
+    ///
+    /// For more information, refer to [RFC 7807 § 3.1](https://datatracker.ietf.org/doc/html/rfc7807#section-3.1)
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub title: Option<String>,
+    /// HTTP status code generated by the ACME provider for this occurrence of the error
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub status: Option<u16>,
+    /// URI reference identifying this occurrence of the error, e.g. the URL of updated Terms of
+    /// Service for `userActionRequired` errors
+    ///
+    /// For more information, refer to [RFC 8555 § 7.3.3](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3.3)
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub instance: Option<String>,
     /// Optional sub-problem documents
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
     #[cfg_attr(feature = "json", serde(rename = "subproblems"))]
     pub sub_problems: Option<Vec<ProblemDocument>>,
+    /// Additional (e.g. ACME provider specific) members of the problem document
+    ///
+    /// For more information, refer to [RFC 7807 § 3.2](https://datatracker.ietf.org/doc/html/rfc7807#section-3.2)
+    #[cfg(feature = "json")]
+    #[serde(flatten)]
+    extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Error {
+    /// Creates an Error object with the given type and description
+    pub fn new(type_: ErrorType, detail: impl Into<String>) -> Error {
+        Error {
+            type_,
+            detail: Some(detail.into()),
+            title: None,
+            status: None,
+            instance: None,
+            sub_problems: None,
+            #[cfg(feature = "json")]
+            extensions: serde_json::Map::new(),
+        }
+    }
 }
 
 #[cfg(feature = "json")]
@@ -28,11 +66,50 @@ impl Error {
     }
 
     /// Serializes an Error object to a JSON String
-    pub fn to_string(&self) -> Result<String, serde_json::error::Error> {
+    ///
+    /// Unlike the other ACME objects, this method is not named `to_string`, as it would shadow the
+    /// [`Display`](std::fmt::Display) implementation's `to_string`.
+    pub fn to_json(&self) -> Result<String, serde_json::error::Error> {
         serde_json::to_string(self)
     }
+
+    /// Returns the additional members of the problem document
+    ///
+    /// For more information, refer to [RFC 7807 § 3.2](https://datatracker.ietf.org/doc/html/rfc7807#section-3.2)
+    pub fn extensions(&self) -> &serde_json::Map<String, serde_json::Value> {
+        &self.extensions
+    }
+
+    /// Returns the value of the given additional member, if present
+    pub fn extension(&self, name: &str) -> Option<&serde_json::Value> {
+        self.extensions.get(name)
+    }
+
+    /// Sets an additional member, replacing any previous value
+    ///
+    /// Standard members (e.g. `type` or `detail`) cannot be set this way; the value would be
+    /// serialized twice.
+    pub fn with_extension(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.extensions.insert(name.into(), value.into());
+        self
+    }
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.detail {
+            Some(detail) => write!(f, "{}: {}", self.type_.to_urn(), detail),
+            None => f.write_str(&self.type_.to_urn()),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 /// Defines an ACME error problem document object
 ///
 /// For more information, refer to [RFC 8555 § 6.7.1](https://datatracker.ietf.org/doc/html/rfc8555#section-6.7.1)
@@ -45,16 +122,96 @@ pub struct ProblemDocument {
     #[cfg_attr(feature = "json", serde(deserialize_with = "error_type_deserialize"))]
     pub type_: ErrorType,
     /// Error description
-    pub detail: String,
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(feature = "json", serde(default))]
+    pub detail: Option<String>,
+    /// Short, human-readable summary of the error type
+    ///
+    /// For more information, refer to [RFC 7807 § 3.1](https://datatracker.ietf.org/doc/html/rfc7807#section-3.1)
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub title: Option<String>,
+    /// HTTP status code generated by the ACME provider for this occurrence of the error
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub status: Option<u16>,
+    /// URI reference identifying this occurrence of the error, e.g. the URL of updated Terms of
+    /// Service for `userActionRequired` errors
+    ///
+    /// For more information, refer to [RFC 8555 § 7.3.3](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3.3)
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub instance: Option<String>,
     /// Optional order identifier associated with the error
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
     pub identifier: Option<super::Identifier>,
+    /// Additional (e.g. ACME provider specific) members of the problem document
+    ///
+    /// For more information, refer to [RFC 7807 § 3.2](https://datatracker.ietf.org/doc/html/rfc7807#section-3.2)
+    #[cfg(feature = "json")]
+    #[serde(flatten)]
+    extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ProblemDocument {
+    /// Creates a ProblemDocument object with the given type, description and identifier
+    pub fn new(
+        type_: ErrorType,
+        detail: impl Into<String>,
+        identifier: Option<super::Identifier>,
+    ) -> ProblemDocument {
+        ProblemDocument {
+            type_,
+            detail: Some(detail.into()),
+            title: None,
+            status: None,
+            instance: None,
+            identifier,
+            #[cfg(feature = "json")]
+            extensions: serde_json::Map::new(),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl ProblemDocument {
+    /// Deserializes a ProblemDocument object from a JSON str
+    pub fn from_str(s: &str) -> Result<ProblemDocument, serde_json::error::Error> {
+        serde_json::from_str(s)
+    }
+
+    /// Serializes a ProblemDocument object to a JSON String
+    pub fn to_string(&self) -> Result<String, serde_json::error::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Returns the additional members of the problem document
+    ///
+    /// For more information, refer to [RFC 7807 § 3.2](https://datatracker.ietf.org/doc/html/rfc7807#section-3.2)
+    pub fn extensions(&self) -> &serde_json::Map<String, serde_json::Value> {
+        &self.extensions
+    }
+
+    /// Returns the value of the given additional member, if present
+    pub fn extension(&self, name: &str) -> Option<&serde_json::Value> {
+        self.extensions.get(name)
+    }
+
+    /// Sets an additional member, replacing any previous value
+    ///
+    /// Standard members (e.g. `type` or `detail`) cannot be set this way; the value would be
+    /// serialized twice.
+    pub fn with_extension(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.extensions.insert(name.into(), value.into());
+        self
+    }
 }
 
 /// Non-exhaustive list of ACME error types
 ///
 /// For more information, refer to [RFC 8555 § 6.7](https://datatracker.ietf.org/doc/html/rfc8555#section-6.7)
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ErrorType {
     AccountDoesNotExist,
     AlreadyRevoked,
@@ -83,44 +240,90 @@ pub enum ErrorType {
     Other(String),
 }
 
+impl ErrorType {
+    /// Parses an ACME error type from its URN, e.g. `urn:ietf:params:acme:error:badNonce`
+    ///
+    /// Unrecognized values are preserved verbatim as [`ErrorType::Other`]. Conversely, an
+    /// [`ErrorType::Other`] holding the URN of a known error type is not preserved: it is parsed
+    /// back as that error type.
+    pub fn from_urn(s: &str) -> ErrorType {
+        use self::ErrorType::*;
+
+        match s.strip_prefix(ERROR_TYPE_URN_PREFIX) {
+            Some("accountDoesNotExist") => AccountDoesNotExist,
+            Some("alreadyRevoked") => AlreadyRevoked,
+            Some("badCSR") => BadCertificateSigningRequest,
+            Some("badNonce") => BadNonce,
+            Some("badPublicKey") => BadPublicKey,
+            Some("badRevocationReason") => BadRevocationReason,
+            Some("badSignatureAlgorithm") => BadSignatureAlgorithm,
+            Some("caa") => CertificationAuthorityAuthorization,
+            Some("compound") => Compound,
+            Some("connection") => Connection,
+            Some("dns") => Dns,
+            Some("externalAccountRequired") => ExternalAccountRequired,
+            Some("incorrectResponse") => IncorrectResponse,
+            Some("invalidContact") => InvalidContact,
+            Some("malformed") => Malformed,
+            Some("orderNotReady") => OrderNotReady,
+            Some("rateLimited") => RateLimited,
+            Some("rejectedIdentifier") => RejectedIdentifier,
+            Some("serverInternal") => ServerInternal,
+            Some("tls") => Tls,
+            Some("unauthorized") => Unauthorized,
+            Some("unsupportedContact") => UnsupportedContact,
+            Some("unsupportedIdentifier") => UnsupportedIdentifier,
+            Some("userActionRequired") => UserActionRequired,
+            _ => Other(s.to_string()),
+        }
+    }
+
+    /// Returns the URN identifying the ACME error type
+    pub fn to_urn(&self) -> String {
+        use self::ErrorType::*;
+
+        let name = match self {
+            AccountDoesNotExist => "accountDoesNotExist",
+            AlreadyRevoked => "alreadyRevoked",
+            BadCertificateSigningRequest => "badCSR",
+            BadNonce => "badNonce",
+            BadPublicKey => "badPublicKey",
+            BadRevocationReason => "badRevocationReason",
+            BadSignatureAlgorithm => "badSignatureAlgorithm",
+            CertificationAuthorityAuthorization => "caa",
+            Compound => "compound",
+            Connection => "connection",
+            Dns => "dns",
+            ExternalAccountRequired => "externalAccountRequired",
+            IncorrectResponse => "incorrectResponse",
+            InvalidContact => "invalidContact",
+            Malformed => "malformed",
+            OrderNotReady => "orderNotReady",
+            RateLimited => "rateLimited",
+            RejectedIdentifier => "rejectedIdentifier",
+            ServerInternal => "serverInternal",
+            Tls => "tls",
+            Unauthorized => "unauthorized",
+            UnsupportedContact => "unsupportedContact",
+            UnsupportedIdentifier => "unsupportedIdentifier",
+            UserActionRequired => "userActionRequired",
+            Other(s) => return s.clone(),
+        };
+
+        format!("{}{}", ERROR_TYPE_URN_PREFIX, name)
+    }
+}
+
+const ERROR_TYPE_URN_PREFIX: &str = "urn:ietf:params:acme:error:";
+
 #[cfg(feature = "json")]
 fn error_type_deserialize<'de, D>(deserializer: D) -> Result<ErrorType, D::Error>
 where
     D: Deserializer<'de>,
 {
-    use self::ErrorType::*;
     let s = String::deserialize(deserializer)?;
 
-    match s.starts_with("urn:ietf:params:acme:error:") {
-        true => match s.strip_prefix("urn:ietf:params:acme:error:").unwrap() {
-            "accountDoesNotExist" => Ok(AccountDoesNotExist),
-            "alreadyRevoked" => Ok(AccountDoesNotExist),
-            "badCSR" => Ok(AccountDoesNotExist),
-            "badNonce" => Ok(AccountDoesNotExist),
-            "badPublicKey" => Ok(AccountDoesNotExist),
-            "badRevocationReason" => Ok(AccountDoesNotExist),
-            "badSignatureAlgorithm" => Ok(AccountDoesNotExist),
-            "caa" => Ok(AccountDoesNotExist),
-            "compound" => Ok(AccountDoesNotExist),
-            "connection" => Ok(AccountDoesNotExist),
-            "dns" => Ok(AccountDoesNotExist),
-            "externalAccountRequired" => Ok(AccountDoesNotExist),
-            "incorrectResponse" => Ok(AccountDoesNotExist),
-            "invalidContact" => Ok(AccountDoesNotExist),
-            "malformed" => Ok(AccountDoesNotExist),
-            "orderNotReady" => Ok(AccountDoesNotExist),
-            "rateLimited" => Ok(AccountDoesNotExist),
-            "rejectedIdentifier" => Ok(AccountDoesNotExist),
-            "serverInternal" => Ok(AccountDoesNotExist),
-            "tls" => Ok(AccountDoesNotExist),
-            "unauthorized" => Ok(AccountDoesNotExist),
-            "unsupportedContact" => Ok(AccountDoesNotExist),
-            "unsupportedIdentifier" => Ok(AccountDoesNotExist),
-            "userActionRequired" => Ok(AccountDoesNotExist),
-            _ => Ok(Other(s)),
-        },
-        false => Ok(Other(s)),
-    }
+    Ok(ErrorType::from_urn(&s))
 }
 
 #[cfg(feature = "json")]
@@ -128,43 +331,162 @@ fn error_type_serialize<S>(type_: &ErrorType, serializer: S) -> Result<S::Ok, S:
 where
     S: Serializer,
 {
-    use self::ErrorType::*;
-
-    serializer.serialize_str(&match type_ {
-        Other(s) => s.to_string(),
-        _ => {
-            format!(
-                "urn:ietf:params:acme:error:{}",
-                match type_ {
-                    AccountDoesNotExist => "accountDoesNotExist",
-                    AlreadyRevoked => "alreadyRevoked",
-                    BadCertificateSigningRequest => "badCSR",
-                    BadNonce => "badNonce",
-                    BadPublicKey => "badPublicKey",
-                    BadRevocationReason => "badRevocationReason",
-                    BadSignatureAlgorithm => "badSignatureAlgorithm",
-                    CertificationAuthorityAuthorization => "caa",
-                    Compound => "compound",
-                    Connection => "connection",
-                    Dns => "dns",
-                    ExternalAccountRequired => "externalAccountRequired",
-                    IncorrectResponse => "incorrectResponse",
-                    InvalidContact => "invalidContact",
-                    Malformed => "malformed",
-                    OrderNotReady => "orderNotReady",
-                    RateLimited => "rateLimited",
-                    RejectedIdentifier => "rejectedIdentifier",
-                    ServerInternal => "serverInternal",
-                    Tls => "tls",
-                    Unauthorized => "unauthorized",
-                    UnsupportedContact => "unsupportedContact",
-                    UnsupportedIdentifier => "unsupportedIdentifier",
-                    UserActionRequired => "userActionRequired",
-                    _ => {
-                        panic!("I've truly fucked up")
-                    }
-                }
-            )
+    serializer.serialize_str(&type_.to_urn())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_types() -> Vec<ErrorType> {
+        use self::ErrorType::*;
+
+        vec![
+            AccountDoesNotExist,
+            AlreadyRevoked,
+            BadCertificateSigningRequest,
+            BadNonce,
+            BadPublicKey,
+            BadRevocationReason,
+            BadSignatureAlgorithm,
+            CertificationAuthorityAuthorization,
+            Compound,
+            Connection,
+            Dns,
+            ExternalAccountRequired,
+            IncorrectResponse,
+            InvalidContact,
+            Malformed,
+            OrderNotReady,
+            RateLimited,
+            RejectedIdentifier,
+            ServerInternal,
+            Tls,
+            Unauthorized,
+            UnsupportedContact,
+            UnsupportedIdentifier,
+            UserActionRequired,
+            Other("urn:example:error:custom".to_string()),
+        ]
+    }
+
+    #[test]
+    fn urn_round_trip() {
+        for type_ in error_types() {
+            assert_eq!(ErrorType::from_urn(&type_.to_urn()), type_);
         }
-    })
+    }
+
+    #[test]
+    fn urn_names() {
+        assert_eq!(
+            ErrorType::BadCertificateSigningRequest.to_urn(),
+            "urn:ietf:params:acme:error:badCSR"
+        );
+        assert_eq!(
+            ErrorType::CertificationAuthorityAuthorization.to_urn(),
+            "urn:ietf:params:acme:error:caa"
+        );
+        assert_eq!(
+            ErrorType::from_urn("urn:ietf:params:acme:error:userActionRequired"),
+            ErrorType::UserActionRequired
+        );
+    }
+
+    #[test]
+    fn other_known_urn_is_normalized() {
+        let type_ = ErrorType::Other("urn:ietf:params:acme:error:badNonce".to_string());
+
+        assert_eq!(ErrorType::from_urn(&type_.to_urn()), ErrorType::BadNonce);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn error_round_trip() {
+        for type_ in error_types() {
+            let error = Error::new(type_.clone(), "detail");
+            let error = Error::from_str(&error.to_json().unwrap()).unwrap();
+
+            assert_eq!(error.type_, type_);
+            assert_eq!(error.detail.as_deref(), Some("detail"));
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn problem_document_round_trip() {
+        for type_ in error_types() {
+            let problem = ProblemDocument::new(type_.clone(), "detail", None);
+            let problem = ProblemDocument::from_str(&problem.to_string().unwrap()).unwrap();
+
+            assert_eq!(problem.type_, type_);
+            assert_eq!(problem.detail.as_deref(), Some("detail"));
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn extensions() {
+        let error = Error::new(ErrorType::BadSignatureAlgorithm, "detail")
+            .with_extension("algorithms", vec!["ES256"]);
+        let error = Error::from_str(&error.to_json().unwrap()).unwrap();
+
+        assert_eq!(
+            error.extension("algorithms"),
+            Some(&serde_json::json!(["ES256"]))
+        );
+        assert_eq!(error.extensions().len(), 1);
+
+        let problem = ProblemDocument::from_str(
+            r#"{"type":"urn:ietf:params:acme:error:caa","detail":"detail","example":1}"#,
+        )
+        .unwrap();
+
+        assert_eq!(problem.extension("example"), Some(&serde_json::json!(1)));
+        assert_eq!(problem.extension("detail"), None);
+        assert!(problem
+            .with_extension("example", 2)
+            .to_string()
+            .unwrap()
+            .contains(r#""example":2"#));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn other_known_urn_is_normalized_in_json() {
+        let error = Error::new(
+            ErrorType::Other("urn:ietf:params:acme:error:badNonce".to_string()),
+            "detail",
+        );
+        let error = Error::from_str(&error.to_json().unwrap()).unwrap();
+
+        assert_eq!(error.type_, ErrorType::BadNonce);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn error_without_detail() {
+        let error = Error::from_str(r#"{"type":"urn:ietf:params:acme:error:badNonce"}"#).unwrap();
+
+        assert_eq!(error.type_, ErrorType::BadNonce);
+        assert_eq!(error.detail, None);
+        assert_eq!(error.to_string(), "urn:ietf:params:acme:error:badNonce");
+        assert_eq!(
+            error.to_json().unwrap(),
+            r#"{"type":"urn:ietf:params:acme:error:badNonce"}"#
+        );
+
+        let problem =
+            ProblemDocument::from_str(r#"{"type":"urn:ietf:params:acme:error:caa"}"#).unwrap();
+
+        assert_eq!(problem.detail, None);
+    }
+
+    #[test]
+    fn error_display() {
+        assert_eq!(
+            Error::new(ErrorType::Malformed, "bad request").to_string(),
+            "urn:ietf:params:acme:error:malformed: bad request"
+        );
+    }
 }
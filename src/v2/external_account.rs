@@ -0,0 +1,343 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::hmac;
+
+use super::{JsonWebKey, JsonWebSignature, JsonWebSignatureProtected, JwsAlgorithm, JwsError};
+
+/// Defines an external account binding key, used to bind a new ACME account to an account held
+/// by the ACME provider outside of ACME.
+///
+/// For more information, refer to [RFC 8555 § 7.3.4](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3.4)
+#[derive(Clone)]
+pub struct ExternalAccountBinding {
+    /// Key identifier provided by the ACME provider
+    pub key_id: String,
+    algorithm: JwsAlgorithm,
+    key: Vec<u8>,
+}
+
+impl ExternalAccountBinding {
+    /// Creates an HS256 external account binding key from the key identifier and the
+    /// base64url-encoded MAC key provided by the ACME provider
+    pub fn new(
+        key_id: impl Into<String>,
+        hmac_key: &str,
+    ) -> Result<ExternalAccountBinding, JwsError> {
+        let key = URL_SAFE_NO_PAD
+            .decode(hmac_key.trim_end_matches('='))
+            .map_err(|_| JwsError::InvalidKey)?;
+
+        Ok(ExternalAccountBinding {
+            key_id: key_id.into(),
+            algorithm: JwsAlgorithm::Hs256,
+            key,
+        })
+    }
+
+    /// Sets the MAC algorithm (HS256, HS384 or HS512) used by the binding
+    pub fn with_algorithm(
+        self,
+        algorithm: JwsAlgorithm,
+    ) -> Result<ExternalAccountBinding, JwsError> {
+        hmac_algorithm(&algorithm)?;
+
+        Ok(ExternalAccountBinding { algorithm, ..self })
+    }
+
+    /// Returns the MAC algorithm used by the binding
+    pub fn algorithm(&self) -> &JwsAlgorithm {
+        &self.algorithm
+    }
+
+    /// Produces the binding (to be set as `NewAccount.external_account_binding`) for the account
+    /// key, where `url` is the new account URL (`Directory.new_account`)
+    pub fn sign(&self, account_key: &JsonWebKey, url: &str) -> Result<JsonWebSignature, JwsError> {
+        let protected = JsonWebSignatureProtected {
+            algorithm: self.algorithm.clone(),
+            nonce: None,
+            url: url.to_string(),
+            json_web_key: None,
+            key_id: Some(self.key_id.clone()),
+        };
+        let protected = protected
+            .to_string()
+            .map_err(|e| JwsError::Json(e.to_string()))?;
+        let payload = account_key
+            .to_public()
+            .to_string()
+            .map_err(|e| JwsError::Json(e.to_string()))?;
+
+        let protected = URL_SAFE_NO_PAD.encode(protected);
+        let payload = URL_SAFE_NO_PAD.encode(payload);
+        let key = hmac::Key::new(hmac_algorithm(&self.algorithm)?, &self.key);
+        let signature = hmac::sign(&key, format!("{}.{}", protected, payload).as_bytes());
+
+        Ok(JsonWebSignature {
+            protected,
+            payload,
+            signature: URL_SAFE_NO_PAD.encode(signature.as_ref()),
+        })
+    }
+
+    /// Returns the key identifier of a binding, so that an ACME provider can look up the MAC key
+    /// with which to verify it
+    pub fn binding_key_id(
+        binding: &JsonWebSignature,
+    ) -> Result<String, ExternalAccountBindingError> {
+        binding
+            .decode_protected()?
+            .key_id
+            .ok_or(ExternalAccountBindingError::MissingKeyId)
+    }
+
+    /// Verifies a binding against the protected header of the new account request containing it
+    ///
+    /// The binding must be MAC-ed by this key using its algorithm, use the same URL as the new
+    /// account request and contain the same account key as the new account request's `jwk`.
+    pub fn verify(
+        &self,
+        binding: &JsonWebSignature,
+        outer: &JsonWebSignatureProtected,
+    ) -> Result<(), ExternalAccountBindingError> {
+        let protected = binding.decode_protected()?;
+
+        if protected.algorithm != self.algorithm {
+            return Err(ExternalAccountBindingError::Algorithm(protected.algorithm));
+        }
+
+        if protected.nonce.is_some() {
+            return Err(ExternalAccountBindingError::Nonce);
+        }
+
+        if protected.key_id.as_deref() != Some(self.key_id.as_str()) {
+            return Err(ExternalAccountBindingError::KeyIdMismatch);
+        }
+
+        let key = hmac::Key::new(hmac_algorithm(&self.algorithm)?, &self.key);
+        let signature = URL_SAFE_NO_PAD
+            .decode(&binding.signature)
+            .map_err(|_| JwsError::Base64("signature"))?;
+        hmac::verify(
+            &key,
+            format!("{}.{}", binding.protected, binding.payload).as_bytes(),
+            &signature,
+        )
+        .map_err(|_| ExternalAccountBindingError::InvalidMac)?;
+
+        if protected.url != outer.url {
+            return Err(ExternalAccountBindingError::UrlMismatch);
+        }
+
+        let account_key: JsonWebKey = serde_json::from_slice(&binding.decode_payload()?)
+            .map_err(|e| JwsError::Json(e.to_string()))?;
+        match &outer.json_web_key {
+            Some(outer_key) if outer_key.to_public() == account_key.to_public() => Ok(()),
+            _ => Err(ExternalAccountBindingError::AccountKeyMismatch),
+        }
+    }
+}
+
+impl std::fmt::Debug for ExternalAccountBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExternalAccountBinding")
+            .field("key_id", &self.key_id)
+            .field("algorithm", &self.algorithm)
+            .finish_non_exhaustive()
+    }
+}
+
+fn hmac_algorithm(algorithm: &JwsAlgorithm) -> Result<hmac::Algorithm, JwsError> {
+    match algorithm {
+        JwsAlgorithm::Hs256 => Ok(hmac::HMAC_SHA256),
+        JwsAlgorithm::Hs384 => Ok(hmac::HMAC_SHA384),
+        JwsAlgorithm::Hs512 => Ok(hmac::HMAC_SHA512),
+        _ => Err(JwsError::UnsupportedAlgorithm(algorithm.clone())),
+    }
+}
+
+/// Errors encountered while verifying an external account binding
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExternalAccountBindingError {
+    /// The binding is not a valid JSON web signature
+    Jws(JwsError),
+    /// The binding does not use the MAC algorithm of the key
+    Algorithm(JwsAlgorithm),
+    /// The binding contains a nonce
+    Nonce,
+    /// The binding does not contain a key identifier
+    MissingKeyId,
+    /// The binding key identifier does not match the key
+    KeyIdMismatch,
+    /// The binding MAC is invalid
+    InvalidMac,
+    /// The binding URL does not match the new account request URL
+    UrlMismatch,
+    /// The binding account key does not match the new account request key
+    AccountKeyMismatch,
+}
+
+impl ExternalAccountBindingError {
+    /// Returns the ACME error type an ACME provider should return for this error
+    pub fn error_type(&self) -> super::ErrorType {
+        match self {
+            ExternalAccountBindingError::Jws(error) => error.error_type(),
+            ExternalAccountBindingError::Algorithm(_) => super::ErrorType::BadSignatureAlgorithm,
+            ExternalAccountBindingError::KeyIdMismatch
+            | ExternalAccountBindingError::InvalidMac => super::ErrorType::Unauthorized,
+            _ => super::ErrorType::Malformed,
+        }
+    }
+}
+
+impl From<JwsError> for ExternalAccountBindingError {
+    fn from(error: JwsError) -> ExternalAccountBindingError {
+        ExternalAccountBindingError::Jws(error)
+    }
+}
+
+impl std::fmt::Display for ExternalAccountBindingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExternalAccountBindingError::Jws(error) => {
+                write!(f, "external account binding is invalid: {}", error)
+            }
+            ExternalAccountBindingError::Algorithm(algorithm) => write!(
+                f,
+                "external account binding algorithm {} is not the algorithm of its key",
+                algorithm
+            ),
+            ExternalAccountBindingError::Nonce => {
+                write!(f, "external account binding must not contain a nonce")
+            }
+            ExternalAccountBindingError::MissingKeyId => {
+                write!(f, "external account binding must contain a key identifier")
+            }
+            ExternalAccountBindingError::KeyIdMismatch => {
+                write!(f, "external account binding key identifier is unknown")
+            }
+            ExternalAccountBindingError::InvalidMac => {
+                write!(f, "external account binding MAC is invalid")
+            }
+            ExternalAccountBindingError::UrlMismatch => write!(
+                f,
+                "external account binding URL does not match the request URL"
+            ),
+            ExternalAccountBindingError::AccountKeyMismatch => write!(
+                f,
+                "external account binding key does not match the account key"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExternalAccountBindingError {}
+
+impl From<ExternalAccountBindingError> for super::Error {
+    fn from(error: ExternalAccountBindingError) -> super::Error {
+        super::Error::new(error.error_type(), error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2::SigningKey;
+
+    const HMAC_KEY: &str = "zWNDZM6eQGHWpSRTPal5eIUYFTu7EajVIoguysqZ9wG44nMEtx3MUAsUDkMTQ12W";
+    const URL: &str = "https://example.com/acme/new-account";
+
+    fn account_key() -> JsonWebKey {
+        let pkcs8 = SigningKey::generate_pkcs8(&JwsAlgorithm::Es256).unwrap();
+        SigningKey::from_pkcs8(JwsAlgorithm::Es256, &pkcs8)
+            .unwrap()
+            .public_key()
+    }
+
+    fn outer(account_key: &JsonWebKey, url: &str) -> JsonWebSignatureProtected {
+        JsonWebSignatureProtected {
+            algorithm: JwsAlgorithm::Es256,
+            nonce: Some("nonce".to_string()),
+            url: url.to_string(),
+            json_web_key: Some(account_key.clone()),
+            key_id: None,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let account_key = account_key();
+
+        for algorithm in [
+            JwsAlgorithm::Hs256,
+            JwsAlgorithm::Hs384,
+            JwsAlgorithm::Hs512,
+        ] {
+            let key = ExternalAccountBinding::new("kid-1", HMAC_KEY)
+                .unwrap()
+                .with_algorithm(algorithm)
+                .unwrap();
+            let binding = key.sign(&account_key, URL).unwrap();
+
+            assert_eq!(
+                ExternalAccountBinding::binding_key_id(&binding).unwrap(),
+                "kid-1"
+            );
+            assert_eq!(key.verify(&binding, &outer(&account_key, URL)), Ok(()));
+        }
+    }
+
+    #[test]
+    fn non_mac_algorithm() {
+        assert_eq!(
+            ExternalAccountBinding::new("kid-1", HMAC_KEY)
+                .unwrap()
+                .with_algorithm(JwsAlgorithm::Es256)
+                .unwrap_err(),
+            JwsError::UnsupportedAlgorithm(JwsAlgorithm::Es256)
+        );
+    }
+
+    #[test]
+    fn rejections() {
+        let account_key = account_key();
+        let key = ExternalAccountBinding::new("kid-1", HMAC_KEY).unwrap();
+        let binding = key.sign(&account_key, URL).unwrap();
+        let verify = |key: &ExternalAccountBinding, outer: JsonWebSignatureProtected| {
+            key.verify(&binding, &outer).unwrap_err()
+        };
+
+        let other_mac_key = ExternalAccountBinding::new("kid-1", "c2VjcmV0").unwrap();
+        let error = verify(&other_mac_key, outer(&account_key, URL));
+        assert_eq!(error, ExternalAccountBindingError::InvalidMac);
+        assert_eq!(error.error_type(), crate::v2::ErrorType::Unauthorized);
+
+        let other_key_id = ExternalAccountBinding::new("kid-2", HMAC_KEY).unwrap();
+        assert_eq!(
+            verify(&other_key_id, outer(&account_key, URL)),
+            ExternalAccountBindingError::KeyIdMismatch
+        );
+
+        assert_eq!(
+            verify(
+                &key,
+                outer(&account_key, "https://example.com/acme/new-order")
+            ),
+            ExternalAccountBindingError::UrlMismatch
+        );
+
+        assert_eq!(
+            verify(&key, outer(&self::account_key(), URL)),
+            ExternalAccountBindingError::AccountKeyMismatch
+        );
+
+        let other_algorithm = key.clone().with_algorithm(JwsAlgorithm::Hs384).unwrap();
+        let error = verify(&other_algorithm, outer(&account_key, URL));
+        assert_eq!(
+            error,
+            ExternalAccountBindingError::Algorithm(JwsAlgorithm::Hs256)
+        );
+        assert_eq!(
+            error.error_type(),
+            crate::v2::ErrorType::BadSignatureAlgorithm
+        );
+    }
+}
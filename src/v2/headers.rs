@@ -0,0 +1,882 @@
+use std::time::Duration;
+
+/// Defines the `Link` header field relations used by ACME
+///
+/// For more information, refer to [RFC 8555 § 7.1](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LinkRelation {
+    /// `index`: the directory URL
+    Index,
+    /// `up`: the parent resource (e.g. the issuer certificate or the authorization of a challenge)
+    Up,
+    /// `alternate`: an alternate certificate chain
+    Alternate,
+    /// `next`: the next page of a list (e.g. account orders)
+    Next,
+    /// Any other relation (e.g. `terms-of-service`)
+    Other(String),
+}
+
+impl LinkRelation {
+    /// Returns the relation type name
+    pub fn as_str(&self) -> &str {
+        match self {
+            LinkRelation::Index => "index",
+            LinkRelation::Up => "up",
+            LinkRelation::Alternate => "alternate",
+            LinkRelation::Next => "next",
+            LinkRelation::Other(relation) => relation,
+        }
+    }
+}
+
+impl From<&str> for LinkRelation {
+    fn from(s: &str) -> LinkRelation {
+        match s.to_ascii_lowercase().as_str() {
+            "index" => LinkRelation::Index,
+            "up" => LinkRelation::Up,
+            "alternate" => LinkRelation::Alternate,
+            "next" => LinkRelation::Next,
+            relation => LinkRelation::Other(relation.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for LinkRelation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Defines a `Link` header field value, e.g. `<https://example.com/acme/directory>;rel="index"`
+///
+/// A single header field may contain several comma-separated links, and a link with several
+/// space-separated relations is parsed into one `Link` per relation.
+///
+/// For more information, refer to [RFC 8288 § 3](https://datatracker.ietf.org/doc/html/rfc8288#section-3)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Link {
+    /// Target URL
+    pub url: String,
+    /// Link relation
+    pub relation: LinkRelation,
+}
+
+impl Link {
+    /// Header field name
+    pub const NAME: &'static str = "Link";
+
+    /// Creates a link to the URL with the given relation
+    pub fn new(url: impl Into<String>, relation: LinkRelation) -> Link {
+        Link {
+            url: url.into(),
+            relation,
+        }
+    }
+
+    /// Parses all the links of a header field value
+    ///
+    /// Links without a `rel` parameter are ignored.
+    pub fn parse(value: &str) -> Result<Vec<Link>, HeaderError> {
+        let malformed = || HeaderError::Link(value.to_string());
+        let mut links = vec![];
+
+        for link in split_unquoted(value, ',') {
+            let link = link.trim();
+            if link.is_empty() {
+                continue;
+            }
+
+            let (url, params) = link
+                .strip_prefix('<')
+                .and_then(|link| link.split_once('>'))
+                .ok_or_else(malformed)?;
+
+            for param in split_unquoted(params, ';').skip(1) {
+                let (name, relations) = match param.split_once('=') {
+                    Some((name, relations)) => (name.trim(), relations.trim()),
+                    None => continue,
+                };
+
+                if !name.eq_ignore_ascii_case("rel") {
+                    continue;
+                }
+
+                let relations = match relations.strip_prefix('"') {
+                    Some(relations) => relations.strip_suffix('"').ok_or_else(malformed)?,
+                    None => relations,
+                };
+
+                links.extend(
+                    relations
+                        .split_whitespace()
+                        .map(|relation| Link::new(url, LinkRelation::from(relation))),
+                );
+            }
+        }
+
+        Ok(links)
+    }
+
+    /// Parses the links of several header field values, e.g. all the `Link` header fields of a
+    /// response
+    pub fn parse_all<'a>(
+        values: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<Link>, HeaderError> {
+        let mut links = vec![];
+        for value in values {
+            links.extend(Link::parse(value)?);
+        }
+
+        Ok(links)
+    }
+
+    /// Returns the URL of the first link with the given relation
+    pub fn find<'a>(links: &'a [Link], relation: &LinkRelation) -> Option<&'a str> {
+        links
+            .iter()
+            .find(|link| &link.relation == relation)
+            .map(|link| link.url.as_str())
+    }
+
+    /// Returns the header field value
+    pub fn to_header_value(&self) -> String {
+        format!("<{}>;rel=\"{}\"", self.url, self.relation)
+    }
+}
+
+impl std::fmt::Display for Link {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_header_value())
+    }
+}
+
+fn split_unquoted(s: &str, separator: char) -> impl Iterator<Item = &str> {
+    let mut quoted = false;
+    let mut bracketed = false;
+
+    s.split(move |c| {
+        match c {
+            '"' => quoted = !quoted,
+            '<' if !quoted => bracketed = true,
+            '>' if !quoted => bracketed = false,
+            _ => {}
+        }
+
+        c == separator && !quoted && !bracketed
+    })
+}
+
+/// Defines a `Location` header field value, i.e. the URL of a created resource
+///
+/// The value may be a relative reference, which is resolved against the request URL.
+///
+/// For more information, refer to [RFC 8555 § 7.1](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1)
+/// and [RFC 9110 § 10.2.2](https://datatracker.ietf.org/doc/html/rfc9110#section-10.2.2)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Location(pub String);
+
+impl Location {
+    /// Header field name
+    pub const NAME: &'static str = "Location";
+
+    /// Parses a header field value
+    pub fn parse(value: &str) -> Result<Location, HeaderError> {
+        let value = value.trim();
+        match value.is_empty() || value.bytes().any(|b| b.is_ascii_whitespace()) {
+            true => Err(HeaderError::Location(value.to_string())),
+            false => Ok(Location(value.to_string())),
+        }
+    }
+
+    /// Returns the header field value
+    pub fn to_header_value(&self) -> String {
+        self.0.clone()
+    }
+
+    /// Resolves the value, which may be a relative reference, against the (absolute) request URL
+    ///
+    /// For more information, refer to [RFC 3986 § 5.2](https://datatracker.ietf.org/doc/html/rfc3986#section-5.2)
+    pub fn resolve(&self, request_url: &str) -> String {
+        let reference = self.0.as_str();
+        if reference.is_empty() {
+            return request_url
+                .split('#')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+        }
+
+        let (scheme, base) = match request_url.split_once("://") {
+            Some(_) if has_scheme(reference) => return reference.to_string(),
+            Some(base) => base,
+            None => return reference.to_string(),
+        };
+
+        if let Some(reference) = reference.strip_prefix("//") {
+            return format!("{}://{}", scheme, reference);
+        }
+
+        let (authority, base_path) =
+            base.split_at(base.find(['/', '?', '#']).unwrap_or(base.len()));
+        let base_path = &base_path[..base_path.find(['?', '#']).unwrap_or(base_path.len())];
+        let (path, suffix) =
+            reference.split_at(reference.find(['?', '#']).unwrap_or(reference.len()));
+
+        let path = match path {
+            "" => base_path.to_string(),
+            path if path.starts_with('/') => path.to_string(),
+            path => match base_path.rfind('/') {
+                Some(i) => format!("{}{}", &base_path[..=i], path),
+                None => format!("/{}", path),
+            },
+        };
+
+        format!(
+            "{}://{}{}{}",
+            scheme,
+            authority,
+            remove_dot_segments(&path),
+            suffix
+        )
+    }
+}
+
+fn has_scheme(reference: &str) -> bool {
+    match reference.split_once(':') {
+        Some((scheme, _)) => {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+fn remove_dot_segments(path: &str) -> String {
+    let segments: Vec<&str> = path.split('/').collect();
+    let mut output = vec![];
+
+    for (i, segment) in segments.iter().enumerate() {
+        match *segment {
+            "." | ".." => {
+                if *segment == ".." && output.len() > 1 {
+                    output.pop();
+                }
+                if i == segments.len() - 1 {
+                    output.push("");
+                }
+            }
+            segment => output.push(segment),
+        }
+    }
+
+    output.join("/")
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0.as_str())
+    }
+}
+
+/// Defines a `Replay-Nonce` header field value, which must be a non-empty base64url string
+///
+/// For more information, refer to [RFC 8555 § 6.5.1](https://datatracker.ietf.org/doc/html/rfc8555#section-6.5.1)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ReplayNonce(pub String);
+
+impl ReplayNonce {
+    /// Header field name
+    pub const NAME: &'static str = "Replay-Nonce";
+
+    /// Parses a header field value
+    pub fn parse(value: &str) -> Result<ReplayNonce, HeaderError> {
+        let value = value.trim();
+        match !value.is_empty()
+            && value
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        {
+            true => Ok(ReplayNonce(value.to_string())),
+            false => Err(HeaderError::ReplayNonce(value.to_string())),
+        }
+    }
+
+    /// Returns the header field value
+    pub fn to_header_value(&self) -> String {
+        self.0.clone()
+    }
+}
+
+impl std::fmt::Display for ReplayNonce {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Defines a `Retry-After` header field value, either a delay in seconds or an HTTP date
+///
+/// HTTP dates are left as the raw header field value whichever features are enabled. With the
+/// `time` feature enabled, they are validated when parsed, accepting IMF-fixdate values (e.g.
+/// `Sun, 06 Nov 1994 08:49:37 GMT`) and the obsolete RFC 850 and asctime forms (e.g.
+/// `Sunday, 06-Nov-94 08:49:37 GMT` and `Sun Nov  6 08:49:37 1994`), and can be converted to and
+/// from `time::OffsetDateTime` values.
+///
+/// For more information, refer to [RFC 9110 § 10.2.3](https://datatracker.ietf.org/doc/html/rfc9110#section-10.2.3)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RetryAfter {
+    /// Delay after which to retry
+    Delay(Duration),
+    /// HTTP date after which to retry
+    Date(String),
+}
+
+impl RetryAfter {
+    /// Header field name
+    pub const NAME: &'static str = "Retry-After";
+
+    /// Parses a header field value
+    pub fn parse(value: &str) -> Result<RetryAfter, HeaderError> {
+        let value = value.trim();
+        if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+            return value
+                .parse()
+                .map(|seconds| RetryAfter::Delay(Duration::from_secs(seconds)))
+                .map_err(|_| HeaderError::RetryAfter(value.to_string()));
+        }
+
+        match is_http_date(value) {
+            true => Ok(RetryAfter::Date(value.to_string())),
+            false => Err(HeaderError::RetryAfter(value.to_string())),
+        }
+    }
+
+    /// Returns the delay after which to retry, or `None` if the date cannot be interpreted (i.e.
+    /// without the `time` feature)
+    ///
+    /// Dates in the past result in a zero delay.
+    pub fn delay(&self) -> Option<Duration> {
+        match self {
+            RetryAfter::Delay(delay) => Some(*delay),
+            #[cfg(feature = "time")]
+            RetryAfter::Date(_) => Some(
+                (self.date()? - time::OffsetDateTime::now_utc())
+                    .try_into()
+                    .unwrap_or(Duration::ZERO),
+            ),
+            #[cfg(not(feature = "time"))]
+            RetryAfter::Date(_) => None,
+        }
+    }
+
+    /// Returns the header field value
+    pub fn to_header_value(&self) -> String {
+        match self {
+            RetryAfter::Delay(delay) => delay.as_secs().to_string(),
+            RetryAfter::Date(date) => date.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+impl RetryAfter {
+    /// Creates an HTTP date value, formatted as an IMF-fixdate
+    pub fn at(date: time::OffsetDateTime) -> RetryAfter {
+        RetryAfter::Date(format_http_date(date))
+    }
+
+    /// Returns the date after which to retry, or `None` for delays and invalid dates
+    pub fn date(&self) -> Option<time::OffsetDateTime> {
+        match self {
+            RetryAfter::Delay(_) => None,
+            RetryAfter::Date(date) => parse_http_date(date),
+        }
+    }
+}
+
+impl std::fmt::Display for RetryAfter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_header_value())
+    }
+}
+
+#[cfg(feature = "time")]
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+#[cfg(feature = "time")]
+fn is_http_date(value: &str) -> bool {
+    parse_http_date(value).is_some()
+}
+
+#[cfg(feature = "time")]
+fn parse_http_date(value: &str) -> Option<time::OffsetDateTime> {
+    parse_imf_fixdate(value)
+        .or_else(|| parse_rfc850_date(value))
+        .or_else(|| parse_asctime_date(value))
+}
+
+/// Parses an IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`
+#[cfg(feature = "time")]
+fn parse_imf_fixdate(value: &str) -> Option<time::OffsetDateTime> {
+    let (weekday, value) = value.split_once(", ")?;
+    let mut fields = value.split(' ');
+    let (day, month, year, clock, zone) = (
+        fields.next()?,
+        fields.next()?,
+        fields.next()?,
+        fields.next()?,
+        fields.next()?,
+    );
+    if fields.next().is_some() || zone != "GMT" {
+        return None;
+    }
+
+    let date = http_date(digits(day, 2)?, month, digits(year, 4)?)?;
+    match date.weekday().to_string()[..3] == *weekday {
+        true => http_date_time(date, clock),
+        false => None,
+    }
+}
+
+/// Parses an obsolete RFC 850 date, e.g. `Sunday, 06-Nov-94 08:49:37 GMT`
+///
+/// Two-digit years which would be more than 50 years in the future are in the previous century.
+#[cfg(feature = "time")]
+fn parse_rfc850_date(value: &str) -> Option<time::OffsetDateTime> {
+    let (weekday, value) = value.split_once(", ")?;
+    let mut fields = value.split(' ');
+    let (date, clock, zone) = (fields.next()?, fields.next()?, fields.next()?);
+    if fields.next().is_some() || zone != "GMT" {
+        return None;
+    }
+
+    let mut date = date.split('-');
+    let (day, month, year) = (date.next()?, date.next()?, date.next()?);
+    if date.next().is_some() {
+        return None;
+    }
+
+    let this_year = time::OffsetDateTime::now_utc().year();
+    let mut year = this_year - this_year.rem_euclid(100) + digits::<i32>(year, 2)?;
+    if year > this_year + 50 {
+        year -= 100;
+    }
+
+    let date = http_date(digits(day, 2)?, month, year)?;
+    match date.weekday().to_string() == weekday {
+        true => http_date_time(date, clock),
+        false => None,
+    }
+}
+
+/// Parses an obsolete asctime date, e.g. `Sun Nov  6 08:49:37 1994`
+#[cfg(feature = "time")]
+fn parse_asctime_date(value: &str) -> Option<time::OffsetDateTime> {
+    let (weekday, value) = value.split_once(' ')?;
+    let (month, value) = value.split_once(' ')?;
+    let (day, value) = match value.strip_prefix(' ') {
+        Some(value) => (digits(value.get(..1)?, 1)?, value.get(1..)?),
+        None => (digits(value.get(..2)?, 2)?, value.get(2..)?),
+    };
+
+    let mut fields = value.strip_prefix(' ')?.split(' ');
+    let (clock, year) = (fields.next()?, fields.next()?);
+    if fields.next().is_some() {
+        return None;
+    }
+
+    let date = http_date(day, month, digits(year, 4)?)?;
+    match date.weekday().to_string()[..3] == *weekday {
+        true => http_date_time(date, clock),
+        false => None,
+    }
+}
+
+#[cfg(feature = "time")]
+fn http_date(day: u8, month: &str, year: i32) -> Option<time::Date> {
+    let month = MONTHS.iter().position(|m| *m == month)? as u8 + 1;
+    time::Date::from_calendar_date(year, time::Month::try_from(month).ok()?, day).ok()
+}
+
+#[cfg(feature = "time")]
+fn http_date_time(date: time::Date, clock: &str) -> Option<time::OffsetDateTime> {
+    let mut clock = clock.split(':').map(|field| digits(field, 2));
+    let time = time::Time::from_hms(clock.next()??, clock.next()??, clock.next()??).ok()?;
+    if clock.next().is_some() {
+        return None;
+    }
+
+    Some(time::PrimitiveDateTime::new(date, time).assume_utc())
+}
+
+/// Parses a fixed number of ASCII digits
+#[cfg(feature = "time")]
+fn digits<T: std::str::FromStr>(value: &str, len: usize) -> Option<T> {
+    match value.len() == len && value.bytes().all(|b| b.is_ascii_digit()) {
+        true => value.parse().ok(),
+        false => None,
+    }
+}
+
+#[cfg(feature = "time")]
+fn format_http_date(date: time::OffsetDateTime) -> String {
+    let date = date.to_offset(time::UtcOffset::UTC);
+
+    format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        &date.weekday().to_string()[..3],
+        date.day(),
+        MONTHS[date.month() as usize - 1],
+        date.year(),
+        date.hour(),
+        date.minute(),
+        date.second(),
+    )
+}
+
+#[cfg(not(feature = "time"))]
+fn is_http_date(value: &str) -> bool {
+    // IMF-fixdate and RFC 850 dates end with the zone, asctime dates with a four-digit year
+    let asctime = value.split_whitespace().count() == 5
+        && value
+            .rsplit_once(' ')
+            .is_some_and(|(_, year)| year.len() == 4 && year.bytes().all(|b| b.is_ascii_digit()));
+
+    value.ends_with(" GMT") || asctime
+}
+
+/// Defines the media types of ACME request and response bodies
+///
+/// For more information, refer to [RFC 8555 § 6.2](https://datatracker.ietf.org/doc/html/rfc8555#section-6.2)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ContentType {
+    /// `application/jose+json`: JWS-signed requests
+    JoseJson,
+    /// `application/problem+json`: error responses
+    ProblemJson,
+    /// `application/json`: resources
+    Json,
+    /// `application/pem-certificate-chain`: certificate downloads
+    PemCertificateChain,
+}
+
+impl ContentType {
+    /// Header field name
+    pub const NAME: &'static str = "Content-Type";
+
+    /// Parses a header field value, ignoring any parameters (e.g. `charset`)
+    pub fn parse(value: &str) -> Result<ContentType, HeaderError> {
+        let media_type = value.split(';').next().unwrap_or_default().trim();
+
+        match media_type.to_ascii_lowercase().as_str() {
+            "application/jose+json" => Ok(ContentType::JoseJson),
+            "application/problem+json" => Ok(ContentType::ProblemJson),
+            "application/json" => Ok(ContentType::Json),
+            "application/pem-certificate-chain" => Ok(ContentType::PemCertificateChain),
+            _ => Err(HeaderError::ContentType(value.trim().to_string())),
+        }
+    }
+
+    /// Returns the media type
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentType::JoseJson => "application/jose+json",
+            ContentType::ProblemJson => "application/problem+json",
+            ContentType::Json => "application/json",
+            ContentType::PemCertificateChain => "application/pem-certificate-chain",
+        }
+    }
+}
+
+impl std::fmt::Display for ContentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Errors encountered while parsing a header field value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HeaderError {
+    /// The `Link` header field value is invalid
+    Link(String),
+    /// The `Location` header field value is invalid
+    Location(String),
+    /// The `Replay-Nonce` header field value is invalid
+    ReplayNonce(String),
+    /// The `Retry-After` header field value is invalid
+    RetryAfter(String),
+    /// The `Content-Type` header field value is invalid or not used by ACME
+    ContentType(String),
+}
+
+impl HeaderError {
+    /// Returns the ACME error type an ACME provider should return for this error
+    pub fn error_type(&self) -> super::ErrorType {
+        match self {
+            HeaderError::ReplayNonce(_) => super::ErrorType::BadNonce,
+            _ => super::ErrorType::Malformed,
+        }
+    }
+}
+
+impl std::fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, value) = match self {
+            HeaderError::Link(value) => (Link::NAME, value),
+            HeaderError::Location(value) => (Location::NAME, value),
+            HeaderError::ReplayNonce(value) => (ReplayNonce::NAME, value),
+            HeaderError::RetryAfter(value) => (RetryAfter::NAME, value),
+            HeaderError::ContentType(value) => (ContentType::NAME, value),
+        };
+
+        write!(f, "invalid {} header field value: {}", name, value)
+    }
+}
+
+impl std::error::Error for HeaderError {}
+
+impl From<HeaderError> for super::Error {
+    fn from(error: HeaderError) -> super::Error {
+        super::Error::new(error.error_type(), error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link() {
+        let links = Link::parse(
+            r#"<https://example.com/acme/directory>;rel="index", <https://example.com/acme/cert/1>; rel="up alternate""#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            links,
+            vec![
+                Link::new("https://example.com/acme/directory", LinkRelation::Index),
+                Link::new("https://example.com/acme/cert/1", LinkRelation::Up),
+                Link::new("https://example.com/acme/cert/1", LinkRelation::Alternate),
+            ]
+        );
+        assert_eq!(
+            Link::find(&links, &LinkRelation::Up),
+            Some("https://example.com/acme/cert/1")
+        );
+        assert_eq!(Link::find(&links, &LinkRelation::Next), None);
+        assert_eq!(
+            links[0].to_header_value(),
+            r#"<https://example.com/acme/directory>;rel="index""#
+        );
+
+        let links = Link::parse_all([
+            r#"<https://example.com/tos>;rel="terms-of-service""#,
+            r#"<https://example.com/acme/orders?cursor=2>;title="a, b";rel=next"#,
+            "<https://example.com/acme/other>",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            links,
+            vec![
+                Link::new(
+                    "https://example.com/tos",
+                    LinkRelation::Other("terms-of-service".to_string())
+                ),
+                Link::new(
+                    "https://example.com/acme/orders?cursor=2",
+                    LinkRelation::Next
+                ),
+            ]
+        );
+
+        for value in [
+            "https://example.com/acme/directory;rel=index",
+            "<https://example.com/acme/directory;rel=index",
+            r#"<https://example.com/acme/directory>;rel="index"#,
+        ] {
+            assert_eq!(
+                Link::parse(value),
+                Err(HeaderError::Link(value.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn location() {
+        let location = Location::parse(" https://example.com/acme/order/1 ").unwrap();
+        assert_eq!(
+            location.to_header_value(),
+            "https://example.com/acme/order/1"
+        );
+
+        assert_eq!(
+            Location::parse(" "),
+            Err(HeaderError::Location(String::new()))
+        );
+
+        assert_eq!(
+            Location::parse("https://example.com/acme/order 1"),
+            Err(HeaderError::Location(
+                "https://example.com/acme/order 1".to_string()
+            ))
+        );
+
+        // Relative references are allowed
+        let location = Location::parse("/acme/order/1").unwrap();
+        assert_eq!(location.to_header_value(), "/acme/order/1");
+
+        for (location, resolved) in [
+            ("https://ca.example/order/1", "https://ca.example/order/1"),
+            ("//ca.example/order/1", "https://ca.example/order/1"),
+            ("/acme/order/1", "https://example.com/acme/order/1"),
+            ("order/1?x=1", "https://example.com/acme/order/1?x=1"),
+            ("../order/./1", "https://example.com/order/1"),
+            ("../../..", "https://example.com/"),
+            ("", "https://example.com/acme/new-order?a=b"),
+        ] {
+            assert_eq!(
+                Location(location.to_string()).resolve("https://example.com/acme/new-order?a=b"),
+                resolved
+            );
+        }
+        assert_eq!(
+            Location("order/1".to_string()).resolve("https://example.com"),
+            "https://example.com/order/1"
+        );
+    }
+
+    #[test]
+    fn replay_nonce() {
+        assert_eq!(
+            ReplayNonce::parse(" oFvnlFP1wIhRlYS2jTaXbA-_ "),
+            Ok(ReplayNonce("oFvnlFP1wIhRlYS2jTaXbA-_".to_string()))
+        );
+
+        for value in ["", "oFvnlFP1wIhRlYS2jTaXbA==", "oFvnlFP1+wIhRlYS2jTaXbA"] {
+            let error = ReplayNonce::parse(value).unwrap_err();
+            assert_eq!(error, HeaderError::ReplayNonce(value.to_string()));
+            assert_eq!(error.error_type(), crate::v2::ErrorType::BadNonce);
+        }
+    }
+
+    #[test]
+    fn retry_after_delay() {
+        let retry_after = RetryAfter::parse(" 120 ").unwrap();
+        assert_eq!(retry_after, RetryAfter::Delay(Duration::from_secs(120)));
+        assert_eq!(retry_after.delay(), Some(Duration::from_secs(120)));
+        assert_eq!(retry_after.to_header_value(), "120");
+
+        for value in ["", "-1", "1.5", "99999999999999999999999", "tomorrow"] {
+            assert_eq!(
+                RetryAfter::parse(value),
+                Err(HeaderError::RetryAfter(value.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn retry_after_date() {
+        for value in [
+            "Sun, 06 Nov 1994 08:49:37 GMT",
+            "Sunday, 06-Nov-94 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 1994",
+        ] {
+            let retry_after = RetryAfter::parse(value).unwrap();
+            assert_eq!(retry_after, RetryAfter::Date(value.to_string()));
+            assert_eq!(retry_after.to_header_value(), value);
+
+            #[cfg(feature = "time")]
+            assert_eq!(retry_after.delay(), Some(Duration::ZERO));
+            #[cfg(not(feature = "time"))]
+            assert_eq!(retry_after.delay(), None);
+        }
+
+        for value in ["Sun Nov  6 08:49:37", "06 Nov 1994"] {
+            assert_eq!(
+                RetryAfter::parse(value),
+                Err(HeaderError::RetryAfter(value.to_string()))
+            );
+        }
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn retry_after_date_time() {
+        let date = time::OffsetDateTime::from_unix_timestamp(784111777).unwrap();
+
+        for value in [
+            "Sun, 06 Nov 1994 08:49:37 GMT",
+            "Sunday, 06-Nov-94 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 1994",
+            "Sun Nov 06 08:49:37 1994",
+        ] {
+            assert_eq!(RetryAfter::parse(value).unwrap().date(), Some(date));
+        }
+
+        assert_eq!(
+            RetryAfter::at(date),
+            RetryAfter::Date("Sun, 06 Nov 1994 08:49:37 GMT".to_string())
+        );
+        assert_eq!(RetryAfter::Delay(Duration::ZERO).date(), None);
+
+        // Two-digit years are interpreted as at most 50 years in the future
+        let date = time::OffsetDateTime::from_unix_timestamp(1893456000).unwrap();
+        assert_eq!(
+            RetryAfter::parse("Tuesday, 01-Jan-30 00:00:00 GMT")
+                .unwrap()
+                .date(),
+            Some(date)
+        );
+
+        let retry_after = RetryAfter::at(time::OffsetDateTime::now_utc() + Duration::from_secs(60));
+        assert!(retry_after.delay().unwrap() > Duration::from_secs(50));
+
+        for value in [
+            "Mon, 06 Nov 1994 08:49:37 GMT",
+            "Sun, 6 Nov 1994 08:49:37 GMT",
+            "Sun, 06 Nov 1994 08:49:37 UTC",
+            "Sun, 06 Nov 1994 8:49:37 GMT",
+            "Sun, 06 Nov 1994 08:49:60 GMT",
+            "Sun, 31 Nov 1994 08:49:37 GMT",
+            "Sun, 06 November 1994 08:49:37 GMT",
+            "Sun, 06-Nov-94 08:49:37 GMT",
+            "Sunday, 06-Nov-1994 08:49:37 GMT",
+            "Sunday, 06 Nov 1994 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 94",
+            "Sun Nov 6 08:49:37 1994",
+            "Sun Nov  6 08:49:37 1994 GMT",
+        ] {
+            assert_eq!(
+                RetryAfter::parse(value),
+                Err(HeaderError::RetryAfter(value.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn content_type() {
+        for content_type in [
+            ContentType::JoseJson,
+            ContentType::ProblemJson,
+            ContentType::Json,
+            ContentType::PemCertificateChain,
+        ] {
+            assert_eq!(ContentType::parse(content_type.as_str()), Ok(content_type));
+        }
+
+        assert_eq!(
+            ContentType::parse("Application/Problem+JSON; charset=utf-8"),
+            Ok(ContentType::ProblemJson)
+        );
+        assert_eq!(
+            ContentType::parse(" text/html "),
+            Err(HeaderError::ContentType("text/html".to_string()))
+        );
+    }
+}
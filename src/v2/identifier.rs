@@ -1,8 +1,13 @@
 #[cfg(feature = "json")]
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 
 /// Defines the identifier object in the Order and NewAuthorization resources
-#[derive(Clone, Debug)]
+///
+/// Identifiers are deserialized as received; ACME providers should validate and normalize them
+/// using [`Identifier::normalize`], so that invalid identifiers are reported as such rather than
+/// as malformed requests.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Identifier {
     /// Identifier type
@@ -12,10 +17,377 @@ pub struct Identifier {
     pub value: String,
 }
 
+impl Identifier {
+    /// Creates a validated and normalized identifier of the given type
+    pub fn new(type_: IdentifierType, value: &str) -> Result<Identifier, IdentifierError> {
+        match type_ {
+            IdentifierType::Dns => Identifier::dns(value),
+            IdentifierType::Ip => value
+                .parse()
+                .map(Identifier::ip)
+                .map_err(|_| IdentifierError::InvalidIpAddress(value.to_string())),
+        }
+    }
+
+    /// Returns the validated and normalized form of the identifier
+    pub fn normalize(&self) -> Result<Identifier, IdentifierError> {
+        Identifier::new(self.type_, &self.value)
+    }
+
+    /// Creates a validated and normalized domain name identifier
+    ///
+    /// Internationalized names are converted to their ASCII (punycode) form (requires the `idna`
+    /// feature), the name is lowercased and a trailing dot is removed. A single leading `*.` label
+    /// is accepted as a wildcard.
+    ///
+    /// For more information, refer to [RFC 8555 § 7.1.3](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.3)
+    pub fn dns(name: &str) -> Result<Identifier, IdentifierError> {
+        let trimmed = name.strip_suffix('.').unwrap_or(name);
+        let (wildcard, base) = match trimmed.strip_prefix("*.") {
+            Some(base) => (true, base),
+            None => (false, trimmed),
+        };
+
+        if base.is_empty() {
+            return Err(IdentifierError::Empty);
+        }
+
+        if base.contains('*') {
+            return Err(IdentifierError::InvalidWildcard(name.to_string()));
+        }
+
+        let base = domain_to_ascii(base).ok_or_else(|| IdentifierError::Idna(name.to_string()))?;
+
+        if base.len() > 253 {
+            return Err(IdentifierError::TooLong(name.to_string()));
+        }
+
+        for label in base.split('.') {
+            if label.is_empty()
+                || label.len() > 63
+                || label.starts_with('-')
+                || label.ends_with('-')
+                || !label
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+            {
+                return Err(IdentifierError::InvalidLabel(label.to_string()));
+            }
+        }
+
+        Ok(Identifier {
+            type_: IdentifierType::Dns,
+            value: match wildcard {
+                true => format!("*.{}", base),
+                false => base,
+            },
+        })
+    }
+
+    /// Returns true if the identifier is a wildcard domain name (e.g. `*.example.com`)
+    pub fn is_wildcard(&self) -> bool {
+        matches!(self.type_, IdentifierType::Dns) && self.value.starts_with("*.")
+    }
+
+    /// Returns the identifier value without its wildcard label, if any
+    pub fn base_domain(&self) -> &str {
+        match self.is_wildcard() {
+            true => &self.value[2..],
+            false => &self.value,
+        }
+    }
+
+    /// Creates an IP address identifier, using the textual representation required by RFC 8738
+    ///
+    /// For more information, refer to [RFC 8738 § 3](https://datatracker.ietf.org/doc/html/rfc8738#section-3)
+    pub fn ip(addr: IpAddr) -> Identifier {
+        Identifier {
+            type_: IdentifierType::Ip,
+            value: addr.to_string(),
+        }
+    }
+
+    /// Returns the IP address of an IP address identifier
+    pub fn ip_addr(&self) -> Option<IpAddr> {
+        match self.type_ {
+            IdentifierType::Ip => self.value.parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the reverse DNS name (e.g. `4.3.2.1.in-addr.arpa`) of an IP address identifier,
+    /// as used for the SNI value of tls-alpn-01 validation requests
+    ///
+    /// For more information, refer to [RFC 8738 § 6](https://datatracker.ietf.org/doc/html/rfc8738#section-6)
+    pub fn reverse_dns_name(&self) -> Option<String> {
+        self.ip_addr().map(|addr| reverse_dns_name(&addr))
+    }
+
+    /// Returns true if the challenge type can be used to validate the identifier
+    pub fn supports_challenge(&self, type_: &super::ChallengeType) -> bool {
+        self.type_.supports_challenge(type_)
+    }
+}
+
+#[cfg(feature = "idna")]
+fn domain_to_ascii(name: &str) -> Option<String> {
+    idna::domain_to_ascii(name).ok()
+}
+
+#[cfg(not(feature = "idna"))]
+fn domain_to_ascii(name: &str) -> Option<String> {
+    match name.is_ascii() {
+        true => Some(name.to_ascii_lowercase()),
+        false => None,
+    }
+}
+
+/// Returns the reverse DNS name of an IP address, i.e. a name under `in-addr.arpa` or `ip6.arpa`
+///
+/// For more information, refer to [RFC 8738 § 6](https://datatracker.ietf.org/doc/html/rfc8738#section-6)
+pub fn reverse_dns_name(addr: &IpAddr) -> String {
+    match addr {
+        IpAddr::V4(addr) => {
+            let [a, b, c, d] = addr.octets();
+            format!("{}.{}.{}.{}.in-addr.arpa", d, c, b, a)
+        }
+        IpAddr::V6(addr) => {
+            let mut name = String::with_capacity(72);
+            for octet in addr.octets().iter().rev() {
+                name.push_str(&format!("{:x}.{:x}.", octet & 0xf, octet >> 4));
+            }
+            name.push_str("ip6.arpa");
+            name
+        }
+    }
+}
+
+/// Errors encountered while validating an identifier
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IdentifierError {
+    /// The identifier value is empty
+    Empty,
+    /// The IP address identifier value is not a valid IP address
+    InvalidIpAddress(String),
+    /// The domain name contains a wildcard other than a single leading `*.` label
+    InvalidWildcard(String),
+    /// The domain name contains an empty, overlong or otherwise invalid label
+    InvalidLabel(String),
+    /// The domain name exceeds 253 characters
+    TooLong(String),
+    /// The internationalized domain name could not be converted to its ASCII form
+    Idna(String),
+}
+
+impl IdentifierError {
+    /// Returns the ACME error type an ACME provider should return for this error
+    pub fn error_type(&self) -> super::ErrorType {
+        match self {
+            IdentifierError::Empty | IdentifierError::InvalidIpAddress(_) => {
+                super::ErrorType::Malformed
+            }
+            _ => super::ErrorType::RejectedIdentifier,
+        }
+    }
+}
+
+impl std::fmt::Display for IdentifierError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdentifierError::Empty => write!(f, "identifier value is empty"),
+            IdentifierError::InvalidIpAddress(s) => {
+                write!(f, "identifier is not a valid IP address: {}", s)
+            }
+            IdentifierError::InvalidWildcard(s) => {
+                write!(f, "identifier contains an invalid wildcard: {}", s)
+            }
+            IdentifierError::InvalidLabel(s) => {
+                write!(f, "identifier contains an invalid label: {}", s)
+            }
+            IdentifierError::TooLong(s) => write!(f, "identifier is too long: {}", s),
+            IdentifierError::Idna(s) => {
+                write!(
+                    f,
+                    "identifier is not a valid internationalized domain name: {}",
+                    s
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for IdentifierError {}
+
+impl From<IdentifierError> for super::Error {
+    fn from(error: IdentifierError) -> super::Error {
+        super::Error::new(error.error_type(), error.to_string())
+    }
+}
+
 /// Order and authorization identifier type values
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum IdentifierType {
     #[cfg_attr(feature = "json", serde(rename = "dns"))]
     Dns,
+    /// For more information about IP address identifiers, refer to [RFC 8738](https://datatracker.ietf.org/doc/html/rfc8738)
+    #[cfg_attr(feature = "json", serde(rename = "ip"))]
+    Ip,
+}
+
+impl IdentifierType {
+    /// Returns true if the challenge type can be used to validate identifiers of this type
+    ///
+    /// IP address identifiers can only be validated using http-01 and tls-alpn-01 challenges.
+    ///
+    /// For more information, refer to [RFC 8738 § 7](https://datatracker.ietf.org/doc/html/rfc8738#section-7)
+    pub fn supports_challenge(&self, type_: &super::ChallengeType) -> bool {
+        use super::ChallengeType::*;
+
+        match self {
+            IdentifierType::Dns => matches!(type_, Http01 | Dns01 | TlsAlpn01),
+            IdentifierType::Ip => matches!(type_, Http01 | TlsAlpn01),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2::ErrorType;
+
+    #[test]
+    fn dns_normalization() {
+        assert_eq!(Identifier::dns("Example.COM").unwrap().value, "example.com");
+        assert_eq!(
+            Identifier::dns("example.com.").unwrap().value,
+            "example.com"
+        );
+        assert_eq!(
+            Identifier::dns("*.Example.com.").unwrap().value,
+            "*.example.com"
+        );
+    }
+
+    #[cfg(feature = "idna")]
+    #[test]
+    fn dns_internationalized() {
+        assert_eq!(
+            Identifier::dns("bücher.example").unwrap().value,
+            "xn--bcher-kva.example"
+        );
+    }
+
+    #[cfg(not(feature = "idna"))]
+    #[test]
+    fn dns_internationalized() {
+        assert_eq!(
+            Identifier::dns("bücher.example"),
+            Err(IdentifierError::Idna("bücher.example".to_string()))
+        );
+    }
+
+    #[test]
+    fn dns_wildcard() {
+        let identifier = Identifier::dns("*.example.com").unwrap();
+
+        assert!(identifier.is_wildcard());
+        assert_eq!(identifier.base_domain(), "example.com");
+        assert!(!Identifier::dns("example.com").unwrap().is_wildcard());
+
+        for name in ["*.*.example.com", "www.*.example.com", "*example.com"] {
+            assert_eq!(
+                Identifier::dns(name),
+                Err(IdentifierError::InvalidWildcard(name.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn dns_errors() {
+        assert_eq!(Identifier::dns(""), Err(IdentifierError::Empty));
+        assert_eq!(Identifier::dns("."), Err(IdentifierError::Empty));
+        assert_eq!(
+            Identifier::dns("example..com"),
+            Err(IdentifierError::InvalidLabel(String::new()))
+        );
+        assert_eq!(
+            Identifier::dns("-example.com"),
+            Err(IdentifierError::InvalidLabel("-example".to_string()))
+        );
+        assert_eq!(
+            Identifier::dns("exa_mple.com").unwrap_err().error_type(),
+            ErrorType::RejectedIdentifier
+        );
+    }
+
+    #[test]
+    fn ip() {
+        assert_eq!(
+            Identifier::new(IdentifierType::Ip, "2001:DB8::1")
+                .unwrap()
+                .value,
+            "2001:db8::1"
+        );
+        assert_eq!(
+            Identifier::new(IdentifierType::Ip, "example.com")
+                .unwrap_err()
+                .error_type(),
+            ErrorType::Malformed
+        );
+        assert_eq!(
+            Identifier::ip("192.0.2.1".parse().unwrap())
+                .reverse_dns_name()
+                .unwrap(),
+            "1.2.0.192.in-addr.arpa"
+        );
+
+        let identifier = Identifier::ip("2001:db8::1".parse().unwrap());
+        assert_eq!(identifier.ip_addr(), Some("2001:db8::1".parse().unwrap()));
+        assert_eq!(
+            identifier.reverse_dns_name().unwrap(),
+            "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"
+        );
+        assert!(identifier.supports_challenge(&crate::v2::ChallengeType::Http01));
+        assert!(identifier.supports_challenge(&crate::v2::ChallengeType::TlsAlpn01));
+        assert!(!identifier.supports_challenge(&crate::v2::ChallengeType::Dns01));
+
+        let identifier = Identifier::dns("example.com").unwrap();
+        assert_eq!(identifier.ip_addr(), None);
+        assert_eq!(identifier.reverse_dns_name(), None);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn ip_serialization() {
+        for (addr, json) in [
+            ("192.0.2.1", r#"{"type":"ip","value":"192.0.2.1"}"#),
+            ("2001:db8::1", r#"{"type":"ip","value":"2001:db8::1"}"#),
+        ] {
+            let identifier = Identifier::ip(addr.parse().unwrap());
+            assert_eq!(serde_json::to_string(&identifier).unwrap(), json);
+
+            let deserialized: Identifier = serde_json::from_str(json).unwrap();
+            assert_eq!(deserialized, identifier);
+            assert_eq!(deserialized.type_, IdentifierType::Ip);
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn lenient_deserialization() {
+        let identifier: Identifier =
+            serde_json::from_str(r#"{"type":"dns","value":"*.*.Example.COM"}"#).unwrap();
+
+        assert_eq!(identifier.value, "*.*.Example.COM");
+        assert_eq!(
+            identifier.normalize().unwrap_err().error_type(),
+            ErrorType::RejectedIdentifier
+        );
+
+        let identifier: Identifier =
+            serde_json::from_str(r#"{"type":"dns","value":"Example.COM."}"#).unwrap();
+
+        assert_eq!(identifier.normalize().unwrap().value, "example.com");
+    }
 }
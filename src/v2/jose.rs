@@ -4,15 +4,74 @@ use serde::{Deserialize, Serialize};
 /// Defines a JSON web key object.
 ///
 /// For more information, refer to [RFC 8555 § 6.2](https://datatracker.ietf.org/doc/html/rfc8555#section-6.2)
-#[derive(Clone, Debug)]
+/// and [RFC 7518 § 6](https://datatracker.ietf.org/doc/html/rfc7518#section-6)
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
-pub struct JsonWebKey {
-    #[cfg_attr(feature = "json", serde(rename = "kty"))]
-    pub key_type: String,
-    #[cfg_attr(feature = "json", serde(rename = "e"))]
-    pub exponent: String,
-    #[cfg_attr(feature = "json", serde(rename = "n"))]
-    pub modulus: String,
+#[cfg_attr(feature = "json", serde(tag = "kty"))]
+pub enum JsonWebKey {
+    /// RSA public key
+    #[cfg_attr(feature = "json", serde(rename = "RSA"))]
+    Rsa(RsaJsonWebKey),
+    /// Elliptic curve public key
+    #[cfg_attr(feature = "json", serde(rename = "EC"))]
+    Ec(EcJsonWebKey),
+    /// Octet key pair (e.g. Ed25519) public key
+    ///
+    /// For more information, refer to [RFC 8037 § 2](https://datatracker.ietf.org/doc/html/rfc8037#section-2)
+    #[cfg_attr(feature = "json", serde(rename = "OKP"))]
+    Okp(OkpJsonWebKey),
+}
+
+impl JsonWebKey {
+    /// Returns the key type (`kty`) value, e.g. `RSA`
+    pub fn key_type(&self) -> &'static str {
+        match self {
+            JsonWebKey::Rsa(_) => "RSA",
+            JsonWebKey::Ec(_) => "EC",
+            JsonWebKey::Okp(_) => "OKP",
+        }
+    }
+
+    /// Returns true if the key does not contain any private key members (e.g. `d`, `p`, `q`, `oth`)
+    pub fn is_public(&self) -> bool {
+        match self {
+            JsonWebKey::Rsa(key) => {
+                key.private_exponent.is_none()
+                    && key.first_prime_factor.is_none()
+                    && key.second_prime_factor.is_none()
+                    && key.first_factor_crt_exponent.is_none()
+                    && key.second_factor_crt_exponent.is_none()
+                    && key.first_crt_coefficient.is_none()
+                    && key.other_primes_info.is_none()
+            }
+            JsonWebKey::Ec(key) => key.private_key.is_none(),
+            JsonWebKey::Okp(key) => key.private_key.is_none(),
+        }
+    }
+
+    /// Returns a copy of the key with any private key members removed
+    pub fn to_public(&self) -> JsonWebKey {
+        match self {
+            JsonWebKey::Rsa(key) => JsonWebKey::Rsa(RsaJsonWebKey {
+                private_exponent: None,
+                first_prime_factor: None,
+                second_prime_factor: None,
+                first_factor_crt_exponent: None,
+                second_factor_crt_exponent: None,
+                first_crt_coefficient: None,
+                other_primes_info: None,
+                ..key.clone()
+            }),
+            JsonWebKey::Ec(key) => JsonWebKey::Ec(EcJsonWebKey {
+                private_key: None,
+                ..key.clone()
+            }),
+            JsonWebKey::Okp(key) => JsonWebKey::Okp(OkpJsonWebKey {
+                private_key: None,
+                ..key.clone()
+            }),
+        }
+    }
 }
 
 #[cfg(feature = "json")]
@@ -28,6 +87,216 @@ impl JsonWebKey {
     }
 }
 
+#[cfg(feature = "crypto")]
+impl JsonWebKey {
+    /// Computes the RFC 7638 thumbprint of the key, i.e. the base64url-encoded SHA-256 digest of
+    /// its required members, serialized in lexicographic order without whitespace
+    ///
+    /// For more information, refer to [RFC 7638 § 3](https://datatracker.ietf.org/doc/html/rfc7638#section-3)
+    pub fn thumbprint(&self) -> String {
+        use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+        use sha2::{Digest, Sha256};
+
+        URL_SAFE_NO_PAD.encode(Sha256::digest(self.thumbprint_input().as_bytes()))
+    }
+
+    fn thumbprint_input(&self) -> String {
+        let members: Vec<(&str, &str)> = match self {
+            JsonWebKey::Rsa(key) => vec![
+                ("e", &key.exponent),
+                ("kty", self.key_type()),
+                ("n", &key.modulus),
+            ],
+            JsonWebKey::Ec(key) => vec![
+                ("crv", key.curve.as_str()),
+                ("kty", self.key_type()),
+                ("x", &key.x),
+                ("y", &key.y),
+            ],
+            JsonWebKey::Okp(key) => vec![
+                ("crv", key.curve.as_str()),
+                ("kty", self.key_type()),
+                ("x", &key.x),
+            ],
+        };
+
+        let members: Vec<String> = members
+            .into_iter()
+            .map(|(name, value)| format!("\"{}\":\"{}\"", name, value.escape_default()))
+            .collect();
+
+        format!("{{{}}}", members.join(","))
+    }
+}
+
+/// Defines the members of an RSA JSON web key.
+///
+/// For more information, refer to [RFC 7518 § 6.3](https://datatracker.ietf.org/doc/html/rfc7518#section-6.3)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct RsaJsonWebKey {
+    /// base64url-encoded public exponent
+    #[cfg_attr(feature = "json", serde(rename = "e"))]
+    pub exponent: String,
+    /// base64url-encoded modulus
+    #[cfg_attr(feature = "json", serde(rename = "n"))]
+    pub modulus: String,
+    /// base64url-encoded private exponent, which should never be sent to an ACME provider
+    #[cfg_attr(
+        feature = "json",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    #[cfg_attr(feature = "json", serde(rename = "d"))]
+    pub private_exponent: Option<String>,
+    /// base64url-encoded first prime factor, which should never be sent to an ACME provider
+    #[cfg_attr(
+        feature = "json",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    #[cfg_attr(feature = "json", serde(rename = "p"))]
+    pub first_prime_factor: Option<String>,
+    /// base64url-encoded second prime factor, which should never be sent to an ACME provider
+    #[cfg_attr(
+        feature = "json",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    #[cfg_attr(feature = "json", serde(rename = "q"))]
+    pub second_prime_factor: Option<String>,
+    /// base64url-encoded first factor CRT exponent, which should never be sent to an ACME provider
+    #[cfg_attr(
+        feature = "json",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    #[cfg_attr(feature = "json", serde(rename = "dp"))]
+    pub first_factor_crt_exponent: Option<String>,
+    /// base64url-encoded second factor CRT exponent, which should never be sent to an ACME provider
+    #[cfg_attr(
+        feature = "json",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    #[cfg_attr(feature = "json", serde(rename = "dq"))]
+    pub second_factor_crt_exponent: Option<String>,
+    /// base64url-encoded first CRT coefficient, which should never be sent to an ACME provider
+    #[cfg_attr(
+        feature = "json",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    #[cfg_attr(feature = "json", serde(rename = "qi"))]
+    pub first_crt_coefficient: Option<String>,
+    /// Information about any prime factor beyond the first two, which should never be sent to an
+    /// ACME provider
+    #[cfg_attr(
+        feature = "json",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    #[cfg_attr(feature = "json", serde(rename = "oth"))]
+    pub other_primes_info: Option<Vec<RsaOtherPrimeInfo>>,
+}
+
+/// Defines the members of an additional prime factor of a multi-prime RSA private key.
+///
+/// For more information, refer to [RFC 7518 § 6.3.2.7](https://datatracker.ietf.org/doc/html/rfc7518#section-6.3.2.7)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct RsaOtherPrimeInfo {
+    /// base64url-encoded prime factor
+    #[cfg_attr(feature = "json", serde(rename = "r"))]
+    pub prime_factor: String,
+    /// base64url-encoded factor CRT exponent
+    #[cfg_attr(feature = "json", serde(rename = "d"))]
+    pub factor_crt_exponent: String,
+    /// base64url-encoded factor CRT coefficient
+    #[cfg_attr(feature = "json", serde(rename = "t"))]
+    pub factor_crt_coefficient: String,
+}
+
+/// Defines the members of an elliptic curve JSON web key.
+///
+/// For more information, refer to [RFC 7518 § 6.2](https://datatracker.ietf.org/doc/html/rfc7518#section-6.2)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct EcJsonWebKey {
+    /// Curve
+    #[cfg_attr(feature = "json", serde(rename = "crv"))]
+    pub curve: EllipticCurve,
+    /// base64url-encoded x coordinate
+    pub x: String,
+    /// base64url-encoded y coordinate
+    pub y: String,
+    /// base64url-encoded private key, which should never be sent to an ACME provider
+    #[cfg_attr(
+        feature = "json",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    #[cfg_attr(feature = "json", serde(rename = "d"))]
+    pub private_key: Option<String>,
+}
+
+/// Defines the members of an octet key pair JSON web key.
+///
+/// For more information, refer to [RFC 8037 § 2](https://datatracker.ietf.org/doc/html/rfc8037#section-2)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct OkpJsonWebKey {
+    /// Curve
+    #[cfg_attr(feature = "json", serde(rename = "crv"))]
+    pub curve: OctetKeyPairCurve,
+    /// base64url-encoded public key
+    pub x: String,
+    /// base64url-encoded private key, which should never be sent to an ACME provider
+    #[cfg_attr(
+        feature = "json",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    #[cfg_attr(feature = "json", serde(rename = "d"))]
+    pub private_key: Option<String>,
+}
+
+/// Elliptic curve JSON web key curve values
+///
+/// For more information, refer to [RFC 7518 § 6.2.1.1](https://datatracker.ietf.org/doc/html/rfc7518#section-6.2.1.1)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub enum EllipticCurve {
+    #[cfg_attr(feature = "json", serde(rename = "P-256"))]
+    P256,
+    #[cfg_attr(feature = "json", serde(rename = "P-384"))]
+    P384,
+    #[cfg_attr(feature = "json", serde(rename = "P-521"))]
+    P521,
+}
+
+impl EllipticCurve {
+    /// Returns the curve (`crv`) value, e.g. `P-256`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EllipticCurve::P256 => "P-256",
+            EllipticCurve::P384 => "P-384",
+            EllipticCurve::P521 => "P-521",
+        }
+    }
+}
+
+/// Octet key pair JSON web key curve values
+///
+/// For more information, refer to [RFC 8037 § 2](https://datatracker.ietf.org/doc/html/rfc8037#section-2)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub enum OctetKeyPairCurve {
+    Ed25519,
+    Ed448,
+}
+
+impl OctetKeyPairCurve {
+    /// Returns the curve (`crv`) value, e.g. `Ed25519`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OctetKeyPairCurve::Ed25519 => "Ed25519",
+            OctetKeyPairCurve::Ed448 => "Ed448",
+        }
+    }
+}
+
 /// Defines a JSON web signature object.
 ///
 /// For more information, refer to [RFC 8555 § 6.2](https://datatracker.ietf.org/doc/html/rfc8555#section-6.2)
@@ -59,7 +328,7 @@ impl JsonWebSignature {
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct JsonWebSignatureProtected {
     #[cfg_attr(feature = "json", serde(rename = "alg"))]
-    pub algorithm: String,
+    pub algorithm: JwsAlgorithm,
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
     pub nonce: Option<String>,
     pub url: String,
@@ -83,3 +352,361 @@ impl JsonWebSignatureProtected {
         serde_json::to_string(self)
     }
 }
+
+impl JsonWebSignatureProtected {
+    /// Checks that the signature algorithm is acceptable for an ACME request, i.e. that it is an
+    /// asymmetric algorithm supported by the ACME provider and compatible with the embedded key
+    ///
+    /// For more information, refer to [RFC 8555 § 6.2](https://datatracker.ietf.org/doc/html/rfc8555#section-6.2)
+    pub fn check_algorithm(
+        &self,
+        supported: &[JwsAlgorithm],
+    ) -> Result<(), SignatureAlgorithmError> {
+        if !self.algorithm.is_asymmetric() || !supported.contains(&self.algorithm) {
+            return Err(SignatureAlgorithmError::Unsupported {
+                algorithm: self.algorithm.clone(),
+                supported: supported.to_vec(),
+            });
+        }
+
+        match &self.json_web_key {
+            Some(key) if !self.algorithm.is_compatible_with(key) => {
+                Err(SignatureAlgorithmError::IncompatibleKey {
+                    algorithm: self.algorithm.clone(),
+                    key_type: key.key_type(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// JSON web signature algorithm values
+///
+/// For more information, refer to [RFC 7518 § 3.1](https://datatracker.ietf.org/doc/html/rfc7518#section-3.1)
+/// and [RFC 8037 § 3.1](https://datatracker.ietf.org/doc/html/rfc8037#section-3.1)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json", serde(from = "String", into = "String"))]
+pub enum JwsAlgorithm {
+    /// RSASSA-PKCS1-v1_5 using SHA-256
+    Rs256,
+    /// ECDSA using P-256 and SHA-256
+    Es256,
+    /// ECDSA using P-384 and SHA-384
+    Es384,
+    /// ECDSA using P-521 and SHA-512
+    Es512,
+    /// EdDSA (e.g. Ed25519)
+    EdDsa,
+    /// HMAC using SHA-256
+    Hs256,
+    /// HMAC using SHA-384
+    Hs384,
+    /// HMAC using SHA-512
+    Hs512,
+    /// Algorithm not known to this crate (including `none`)
+    Other(String),
+}
+
+impl JwsAlgorithm {
+    /// Asymmetric algorithms with which this crate can sign and verify ACME requests
+    ///
+    /// ES512 is not included, as P-521 keys are not supported by `JsonWebSignature::verify`.
+    pub const ACME_ALGORITHMS: &'static [JwsAlgorithm] = &[
+        JwsAlgorithm::Rs256,
+        JwsAlgorithm::Es256,
+        JwsAlgorithm::Es384,
+        JwsAlgorithm::EdDsa,
+    ];
+
+    /// Returns the algorithm (`alg`) value, e.g. `ES256`
+    pub fn as_str(&self) -> &str {
+        match self {
+            JwsAlgorithm::Rs256 => "RS256",
+            JwsAlgorithm::Es256 => "ES256",
+            JwsAlgorithm::Es384 => "ES384",
+            JwsAlgorithm::Es512 => "ES512",
+            JwsAlgorithm::EdDsa => "EdDSA",
+            JwsAlgorithm::Hs256 => "HS256",
+            JwsAlgorithm::Hs384 => "HS384",
+            JwsAlgorithm::Hs512 => "HS512",
+            JwsAlgorithm::Other(s) => s,
+        }
+    }
+
+    /// Returns true if the algorithm is an asymmetric (digital signature) algorithm
+    pub fn is_asymmetric(&self) -> bool {
+        use self::JwsAlgorithm::*;
+
+        matches!(self, Rs256 | Es256 | Es384 | Es512 | EdDsa)
+    }
+
+    /// Returns true if the algorithm is a MAC algorithm, which ACME only permits for external
+    /// account bindings
+    ///
+    /// For more information, refer to [RFC 8555 § 7.3.4](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3.4)
+    pub fn is_mac(&self) -> bool {
+        use self::JwsAlgorithm::*;
+
+        matches!(self, Hs256 | Hs384 | Hs512)
+    }
+
+    /// Returns true if the algorithm can be used with the given key
+    pub fn is_compatible_with(&self, key: &JsonWebKey) -> bool {
+        match (self, key) {
+            (JwsAlgorithm::Rs256, JsonWebKey::Rsa(_)) => true,
+            (JwsAlgorithm::Es256, JsonWebKey::Ec(key)) => key.curve == EllipticCurve::P256,
+            (JwsAlgorithm::Es384, JsonWebKey::Ec(key)) => key.curve == EllipticCurve::P384,
+            (JwsAlgorithm::Es512, JsonWebKey::Ec(key)) => key.curve == EllipticCurve::P521,
+            (JwsAlgorithm::EdDsa, JsonWebKey::Okp(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl From<&str> for JwsAlgorithm {
+    fn from(s: &str) -> JwsAlgorithm {
+        match s {
+            "RS256" => JwsAlgorithm::Rs256,
+            "ES256" => JwsAlgorithm::Es256,
+            "ES384" => JwsAlgorithm::Es384,
+            "ES512" => JwsAlgorithm::Es512,
+            "EdDSA" => JwsAlgorithm::EdDsa,
+            "HS256" => JwsAlgorithm::Hs256,
+            "HS384" => JwsAlgorithm::Hs384,
+            "HS512" => JwsAlgorithm::Hs512,
+            _ => JwsAlgorithm::Other(s.to_string()),
+        }
+    }
+}
+
+impl From<String> for JwsAlgorithm {
+    fn from(s: String) -> JwsAlgorithm {
+        JwsAlgorithm::from(s.as_str())
+    }
+}
+
+impl From<JwsAlgorithm> for String {
+    fn from(algorithm: JwsAlgorithm) -> String {
+        match algorithm {
+            JwsAlgorithm::Other(s) => s,
+            _ => algorithm.as_str().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for JwsAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Errors encountered while checking the signature algorithm of an ACME request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignatureAlgorithmError {
+    /// The algorithm is not supported by the ACME provider
+    Unsupported {
+        algorithm: JwsAlgorithm,
+        supported: Vec<JwsAlgorithm>,
+    },
+    /// The algorithm cannot be used with the embedded key
+    IncompatibleKey {
+        algorithm: JwsAlgorithm,
+        key_type: &'static str,
+    },
+}
+
+impl SignatureAlgorithmError {
+    /// Returns the ACME error type an ACME provider should return for this error
+    pub fn error_type(&self) -> super::ErrorType {
+        match self {
+            SignatureAlgorithmError::Unsupported { .. } => super::ErrorType::BadSignatureAlgorithm,
+            SignatureAlgorithmError::IncompatibleKey { .. } => super::ErrorType::Malformed,
+        }
+    }
+}
+
+impl std::fmt::Display for SignatureAlgorithmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureAlgorithmError::Unsupported { algorithm, .. } => {
+                write!(f, "signature algorithm is not supported: {}", algorithm)
+            }
+            SignatureAlgorithmError::IncompatibleKey {
+                algorithm,
+                key_type,
+            } => write!(
+                f,
+                "signature algorithm {} cannot be used with {} keys",
+                algorithm, key_type
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SignatureAlgorithmError {}
+
+impl From<SignatureAlgorithmError> for super::Error {
+    /// Converts the error into an ACME error, listing the supported algorithms in the `algorithms`
+    /// member of `badSignatureAlgorithm` errors (requires the `json` feature)
+    fn from(error: SignatureAlgorithmError) -> super::Error {
+        let problem = super::Error::new(error.error_type(), error.to_string());
+
+        #[cfg(feature = "json")]
+        if let SignatureAlgorithmError::Unsupported { supported, .. } = &error {
+            return problem.with_extension(
+                "algorithms",
+                supported.iter().map(|a| a.as_str()).collect::<Vec<_>>(),
+            );
+        }
+
+        problem
+    }
+}
+
+#[cfg(all(test, feature = "crypto"))]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Returns the example RSA key of RFC 7638 § 3.1
+    pub(crate) fn rfc7638_key() -> JsonWebKey {
+        JsonWebKey::Rsa(RsaJsonWebKey {
+            exponent: "AQAB".to_string(),
+            modulus: concat!(
+                "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tS",
+                "oc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65Y",
+                "GjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdk",
+                "t-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnq",
+                "DKgw",
+            )
+            .to_string(),
+            private_exponent: None,
+            first_prime_factor: None,
+            second_prime_factor: None,
+            first_factor_crt_exponent: None,
+            second_factor_crt_exponent: None,
+            first_crt_coefficient: None,
+            other_primes_info: None,
+        })
+    }
+
+    #[test]
+    fn thumbprint() {
+        assert_eq!(
+            rfc7638_key().thumbprint(),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+    }
+
+    #[test]
+    fn thumbprint_ignores_private_members() {
+        let key = rfc7638_key();
+        let public = match key.clone() {
+            JsonWebKey::Rsa(key) => key,
+            _ => unreachable!(),
+        };
+        let member = || Some("private".to_string());
+
+        for private in [
+            RsaJsonWebKey {
+                private_exponent: member(),
+                ..public.clone()
+            },
+            RsaJsonWebKey {
+                first_prime_factor: member(),
+                ..public.clone()
+            },
+            RsaJsonWebKey {
+                second_prime_factor: member(),
+                ..public.clone()
+            },
+            RsaJsonWebKey {
+                first_factor_crt_exponent: member(),
+                ..public.clone()
+            },
+            RsaJsonWebKey {
+                second_factor_crt_exponent: member(),
+                ..public.clone()
+            },
+            RsaJsonWebKey {
+                first_crt_coefficient: member(),
+                ..public.clone()
+            },
+            RsaJsonWebKey {
+                other_primes_info: Some(vec![]),
+                ..public.clone()
+            },
+        ] {
+            let private = JsonWebKey::Rsa(private);
+
+            assert!(!private.is_public());
+            assert_eq!(private.to_public(), key);
+            assert_eq!(private.thumbprint(), key.thumbprint());
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn rsa_private_members() {
+        let public = rfc7638_key().to_string().unwrap();
+
+        for member in [
+            r#""d":"private""#,
+            r#""p":"private""#,
+            r#""q":"private""#,
+            r#""dp":"private""#,
+            r#""dq":"private""#,
+            r#""qi":"private""#,
+            r#""oth":[{"r":"private","d":"private","t":"private"}]"#,
+        ] {
+            let private = format!("{},{}}}", &public[..public.len() - 1], member);
+            let private = JsonWebKey::from_str(&private).unwrap();
+
+            assert!(!private.is_public(), "{}", member);
+            assert_eq!(private.to_public().to_string().unwrap(), public);
+        }
+    }
+
+    #[test]
+    fn check_algorithm() {
+        let protected = |algorithm: JwsAlgorithm| JsonWebSignatureProtected {
+            algorithm,
+            nonce: None,
+            url: "https://example.com/acme/new-order".to_string(),
+            json_web_key: Some(rfc7638_key()),
+            key_id: None,
+        };
+
+        assert!(protected(JwsAlgorithm::Rs256)
+            .check_algorithm(JwsAlgorithm::ACME_ALGORITHMS)
+            .is_ok());
+
+        for algorithm in [
+            JwsAlgorithm::Es512,
+            JwsAlgorithm::Hs256,
+            JwsAlgorithm::Other("none".to_string()),
+        ] {
+            assert_eq!(
+                protected(algorithm)
+                    .check_algorithm(JwsAlgorithm::ACME_ALGORITHMS)
+                    .unwrap_err()
+                    .error_type(),
+                crate::v2::ErrorType::BadSignatureAlgorithm
+            );
+        }
+
+        assert_eq!(
+            protected(JwsAlgorithm::Es256)
+                .check_algorithm(JwsAlgorithm::ACME_ALGORITHMS)
+                .unwrap_err(),
+            SignatureAlgorithmError::IncompatibleKey {
+                algorithm: JwsAlgorithm::Es256,
+                key_type: "RSA",
+            }
+        );
+        assert!(protected(JwsAlgorithm::Rs256)
+            .check_algorithm(&[JwsAlgorithm::Es256])
+            .is_err());
+    }
+}
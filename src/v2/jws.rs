@@ -0,0 +1,478 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::rand::SystemRandom;
+use ring::signature::{self, KeyPair};
+use serde::Serialize;
+
+use super::{
+    EcJsonWebKey, EllipticCurve, JsonWebKey, JsonWebSignature, JsonWebSignatureProtected,
+    JwsAlgorithm, OctetKeyPairCurve, OkpJsonWebKey, RsaJsonWebKey,
+};
+
+/// Defines a private key used to sign JSON web signatures.
+///
+/// RS256, ES256, ES384 and EdDSA (Ed25519) keys are supported.
+pub struct SigningKey(SigningKeyPair);
+
+enum SigningKeyPair {
+    Rsa(signature::RsaKeyPair),
+    Ecdsa(JwsAlgorithm, signature::EcdsaKeyPair),
+    Ed25519(signature::Ed25519KeyPair),
+}
+
+impl SigningKey {
+    /// Loads a PKCS#8 (DER-encoded) private key for use with the given algorithm
+    pub fn from_pkcs8(algorithm: JwsAlgorithm, pkcs8: &[u8]) -> Result<SigningKey, JwsError> {
+        let rng = SystemRandom::new();
+        let key_pair = match algorithm {
+            JwsAlgorithm::Rs256 => {
+                signature::RsaKeyPair::from_pkcs8(pkcs8).map(SigningKeyPair::Rsa)
+            }
+            JwsAlgorithm::Es256 | JwsAlgorithm::Es384 => {
+                signature::EcdsaKeyPair::from_pkcs8(ecdsa_signing(&algorithm)?, pkcs8, &rng)
+                    .map(|key_pair| SigningKeyPair::Ecdsa(algorithm.clone(), key_pair))
+            }
+            JwsAlgorithm::EdDsa => signature::Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8)
+                .map(SigningKeyPair::Ed25519),
+            _ => return Err(JwsError::UnsupportedAlgorithm(algorithm)),
+        };
+
+        key_pair.map(SigningKey).map_err(|_| JwsError::InvalidKey)
+    }
+
+    /// Generates a new PKCS#8 (DER-encoded) private key for use with the given algorithm
+    ///
+    /// Only ES256, ES384 and EdDSA keys can be generated.
+    pub fn generate_pkcs8(algorithm: &JwsAlgorithm) -> Result<Vec<u8>, JwsError> {
+        let rng = SystemRandom::new();
+        let pkcs8 = match algorithm {
+            JwsAlgorithm::Es256 | JwsAlgorithm::Es384 => {
+                signature::EcdsaKeyPair::generate_pkcs8(ecdsa_signing(algorithm)?, &rng)
+            }
+            JwsAlgorithm::EdDsa => signature::Ed25519KeyPair::generate_pkcs8(&rng),
+            _ => return Err(JwsError::UnsupportedAlgorithm(algorithm.clone())),
+        };
+
+        pkcs8
+            .map(|pkcs8| pkcs8.as_ref().to_vec())
+            .map_err(|_| JwsError::Signing)
+    }
+
+    /// Returns the algorithm used by the key
+    pub fn algorithm(&self) -> JwsAlgorithm {
+        match &self.0 {
+            SigningKeyPair::Rsa(_) => JwsAlgorithm::Rs256,
+            SigningKeyPair::Ecdsa(algorithm, _) => algorithm.clone(),
+            SigningKeyPair::Ed25519(_) => JwsAlgorithm::EdDsa,
+        }
+    }
+
+    /// Returns the public key as a JSON web key
+    pub fn public_key(&self) -> JsonWebKey {
+        match &self.0 {
+            SigningKeyPair::Rsa(key_pair) => {
+                let components: signature::RsaPublicKeyComponents<Vec<u8>> =
+                    key_pair.public().into();
+                JsonWebKey::Rsa(RsaJsonWebKey {
+                    exponent: URL_SAFE_NO_PAD.encode(components.e),
+                    modulus: URL_SAFE_NO_PAD.encode(components.n),
+                    private_exponent: None,
+                    first_prime_factor: None,
+                    second_prime_factor: None,
+                    first_factor_crt_exponent: None,
+                    second_factor_crt_exponent: None,
+                    first_crt_coefficient: None,
+                    other_primes_info: None,
+                })
+            }
+            SigningKeyPair::Ecdsa(algorithm, key_pair) => {
+                // Uncompressed point: 0x04 || x || y
+                let point = &key_pair.public_key().as_ref()[1..];
+                let (x, y) = point.split_at(point.len() / 2);
+                JsonWebKey::Ec(EcJsonWebKey {
+                    curve: match algorithm {
+                        JwsAlgorithm::Es384 => EllipticCurve::P384,
+                        _ => EllipticCurve::P256,
+                    },
+                    x: URL_SAFE_NO_PAD.encode(x),
+                    y: URL_SAFE_NO_PAD.encode(y),
+                    private_key: None,
+                })
+            }
+            SigningKeyPair::Ed25519(key_pair) => JsonWebKey::Okp(OkpJsonWebKey {
+                curve: OctetKeyPairCurve::Ed25519,
+                x: URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref()),
+                private_key: None,
+            }),
+        }
+    }
+
+    /// Signs a message, returning the raw JWS signature
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, JwsError> {
+        let rng = SystemRandom::new();
+        match &self.0 {
+            SigningKeyPair::Rsa(key_pair) => {
+                let mut signature = vec![0; key_pair.public().modulus_len()];
+                key_pair
+                    .sign(&signature::RSA_PKCS1_SHA256, &rng, message, &mut signature)
+                    .map_err(|_| JwsError::Signing)?;
+                Ok(signature)
+            }
+            SigningKeyPair::Ecdsa(_, key_pair) => key_pair
+                .sign(&rng, message)
+                .map(|signature| signature.as_ref().to_vec())
+                .map_err(|_| JwsError::Signing),
+            SigningKeyPair::Ed25519(key_pair) => Ok(key_pair.sign(message).as_ref().to_vec()),
+        }
+    }
+}
+
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("algorithm", &self.algorithm())
+            .finish_non_exhaustive()
+    }
+}
+
+fn ecdsa_signing(
+    algorithm: &JwsAlgorithm,
+) -> Result<&'static signature::EcdsaSigningAlgorithm, JwsError> {
+    match algorithm {
+        JwsAlgorithm::Es256 => Ok(&signature::ECDSA_P256_SHA256_FIXED_SIGNING),
+        JwsAlgorithm::Es384 => Ok(&signature::ECDSA_P384_SHA384_FIXED_SIGNING),
+        _ => Err(JwsError::UnsupportedAlgorithm(algorithm.clone())),
+    }
+}
+
+impl JsonWebSignature {
+    /// Signs a request payload, producing a flattened JSON web signature
+    ///
+    /// For more information, refer to [RFC 8555 § 6.2](https://datatracker.ietf.org/doc/html/rfc8555#section-6.2)
+    pub fn sign<T: Serialize>(
+        protected: &JsonWebSignatureProtected,
+        payload: &T,
+        key: &SigningKey,
+    ) -> Result<JsonWebSignature, JwsError> {
+        let payload = serde_json::to_vec(payload).map_err(|e| JwsError::Json(e.to_string()))?;
+
+        JsonWebSignature::sign_bytes(protected, &payload, key)
+    }
+
+    /// Signs a POST-as-GET request, i.e. a request with an empty payload
+    ///
+    /// For more information, refer to [RFC 8555 § 6.3](https://datatracker.ietf.org/doc/html/rfc8555#section-6.3)
+    pub fn sign_empty(
+        protected: &JsonWebSignatureProtected,
+        key: &SigningKey,
+    ) -> Result<JsonWebSignature, JwsError> {
+        JsonWebSignature::sign_bytes(protected, &[], key)
+    }
+
+    /// Signs a raw payload, producing a flattened JSON web signature
+    pub fn sign_bytes(
+        protected: &JsonWebSignatureProtected,
+        payload: &[u8],
+        key: &SigningKey,
+    ) -> Result<JsonWebSignature, JwsError> {
+        if protected.algorithm != key.algorithm() {
+            return Err(JwsError::IncompatibleKey(protected.algorithm.clone()));
+        }
+
+        let protected = protected
+            .to_string()
+            .map_err(|e| JwsError::Json(e.to_string()))?;
+        let protected = URL_SAFE_NO_PAD.encode(protected);
+        let payload = URL_SAFE_NO_PAD.encode(payload);
+        let signature = key.sign(format!("{}.{}", protected, payload).as_bytes())?;
+
+        Ok(JsonWebSignature {
+            protected,
+            payload,
+            signature: URL_SAFE_NO_PAD.encode(signature),
+        })
+    }
+
+    /// Decodes the protected header without verifying the signature
+    pub fn decode_protected(&self) -> Result<JsonWebSignatureProtected, JwsError> {
+        let protected = decode(&self.protected, "protected")?;
+
+        serde_json::from_slice(&protected).map_err(|e| JwsError::Json(e.to_string()))
+    }
+
+    /// Decodes the payload without verifying the signature
+    pub fn decode_payload(&self) -> Result<Vec<u8>, JwsError> {
+        decode(&self.payload, "payload")
+    }
+
+    /// Verifies the signature using the given public key, returning the decoded protected header
+    /// and payload
+    pub fn verify(
+        &self,
+        key: &JsonWebKey,
+    ) -> Result<(JsonWebSignatureProtected, Vec<u8>), JwsError> {
+        let protected = self.decode_protected()?;
+        let payload = self.decode_payload()?;
+        let signature = decode(&self.signature, "signature")?;
+
+        if !protected.algorithm.is_compatible_with(key) {
+            return Err(JwsError::IncompatibleKey(protected.algorithm));
+        }
+
+        let message = format!("{}.{}", self.protected, self.payload);
+        let verified = match key {
+            JsonWebKey::Rsa(key) => signature::RsaPublicKeyComponents {
+                n: decode(&key.modulus, "n")?,
+                e: decode(&key.exponent, "e")?,
+            }
+            .verify(
+                &signature::RSA_PKCS1_2048_8192_SHA256,
+                message.as_bytes(),
+                &signature,
+            ),
+            JsonWebKey::Ec(key) => {
+                let algorithm: &'static dyn signature::VerificationAlgorithm = match key.curve {
+                    EllipticCurve::P256 => &signature::ECDSA_P256_SHA256_FIXED,
+                    EllipticCurve::P384 => &signature::ECDSA_P384_SHA384_FIXED,
+                    EllipticCurve::P521 => {
+                        return Err(JwsError::UnsupportedAlgorithm(protected.algorithm))
+                    }
+                };
+                let mut point = vec![0x04];
+                point.extend(decode(&key.x, "x")?);
+                point.extend(decode(&key.y, "y")?);
+                signature::UnparsedPublicKey::new(algorithm, point)
+                    .verify(message.as_bytes(), &signature)
+            }
+            JsonWebKey::Okp(key) => match key.curve {
+                OctetKeyPairCurve::Ed25519 => {
+                    signature::UnparsedPublicKey::new(&signature::ED25519, decode(&key.x, "x")?)
+                        .verify(message.as_bytes(), &signature)
+                }
+                OctetKeyPairCurve::Ed448 => {
+                    return Err(JwsError::UnsupportedAlgorithm(protected.algorithm))
+                }
+            },
+        };
+
+        verified.map_err(|_| JwsError::InvalidSignature)?;
+
+        Ok((protected, payload))
+    }
+}
+
+fn decode(value: &str, member: &'static str) -> Result<Vec<u8>, JwsError> {
+    URL_SAFE_NO_PAD
+        .decode(value)
+        .map_err(|_| JwsError::Base64(member))
+}
+
+/// Errors encountered while signing or verifying a JSON web signature
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JwsError {
+    /// A member is not valid unpadded base64url
+    Base64(&'static str),
+    /// The protected header or payload is not valid JSON
+    Json(String),
+    /// The signature algorithm is not supported
+    UnsupportedAlgorithm(JwsAlgorithm),
+    /// The signature algorithm cannot be used with the key
+    IncompatibleKey(JwsAlgorithm),
+    /// The key could not be loaded
+    InvalidKey,
+    /// The signature could not be produced
+    Signing,
+    /// The signature does not match the key
+    InvalidSignature,
+}
+
+impl JwsError {
+    /// Returns the ACME error type an ACME provider should return for this error
+    pub fn error_type(&self) -> super::ErrorType {
+        match self {
+            JwsError::UnsupportedAlgorithm(_) => super::ErrorType::BadSignatureAlgorithm,
+            JwsError::InvalidKey => super::ErrorType::BadPublicKey,
+            JwsError::Signing => super::ErrorType::ServerInternal,
+            _ => super::ErrorType::Malformed,
+        }
+    }
+}
+
+impl std::fmt::Display for JwsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JwsError::Base64(member) => write!(f, "JWS {} is not valid base64url", member),
+            JwsError::Json(e) => write!(f, "JWS contains invalid JSON: {}", e),
+            JwsError::UnsupportedAlgorithm(algorithm) => {
+                write!(f, "JWS algorithm is not supported: {}", algorithm)
+            }
+            JwsError::IncompatibleKey(algorithm) => {
+                write!(f, "JWS algorithm {} does not match the key", algorithm)
+            }
+            JwsError::InvalidKey => write!(f, "JWS key is invalid"),
+            JwsError::Signing => write!(f, "JWS could not be signed"),
+            JwsError::InvalidSignature => write!(f, "JWS signature is invalid"),
+        }
+    }
+}
+
+impl std::error::Error for JwsError {}
+
+impl From<JwsError> for super::Error {
+    fn from(error: JwsError) -> super::Error {
+        super::Error::new(error.error_type(), error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signing_key(algorithm: JwsAlgorithm) -> SigningKey {
+        match algorithm {
+            JwsAlgorithm::Rs256 => {
+                SigningKey::from_pkcs8(algorithm, include_bytes!("testdata/rsa2048.p8")).unwrap()
+            }
+            _ => {
+                let pkcs8 = SigningKey::generate_pkcs8(&algorithm).unwrap();
+                SigningKey::from_pkcs8(algorithm, &pkcs8).unwrap()
+            }
+        }
+    }
+
+    fn protected(algorithm: JwsAlgorithm) -> JsonWebSignatureProtected {
+        JsonWebSignatureProtected {
+            algorithm,
+            nonce: Some("nonce".to_string()),
+            url: "https://example.com/acme/new-order".to_string(),
+            json_web_key: None,
+            key_id: Some("https://example.com/acme/account/1".to_string()),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for algorithm in [
+            JwsAlgorithm::Rs256,
+            JwsAlgorithm::Es256,
+            JwsAlgorithm::Es384,
+            JwsAlgorithm::EdDsa,
+        ] {
+            let key = signing_key(algorithm.clone());
+            let jws =
+                JsonWebSignature::sign(&protected(algorithm.clone()), &"payload", &key).unwrap();
+            let (verified, payload) = jws.verify(&key.public_key()).unwrap();
+
+            assert_eq!(verified.algorithm, algorithm);
+            assert_eq!(verified.url, "https://example.com/acme/new-order");
+            assert_eq!(payload, b"\"payload\"");
+        }
+    }
+
+    #[test]
+    fn round_trip_empty() {
+        let key = signing_key(JwsAlgorithm::EdDsa);
+        let jws = JsonWebSignature::sign_empty(&protected(JwsAlgorithm::EdDsa), &key).unwrap();
+
+        assert_eq!(jws.payload, "");
+        assert_eq!(jws.verify(&key.public_key()).unwrap().1, b"");
+    }
+
+    #[test]
+    fn tampered_payload() {
+        for algorithm in [
+            JwsAlgorithm::Rs256,
+            JwsAlgorithm::Es256,
+            JwsAlgorithm::EdDsa,
+        ] {
+            let key = signing_key(algorithm.clone());
+            let jws = JsonWebSignature::sign(&protected(algorithm), &"payload", &key).unwrap();
+            let jws = JsonWebSignature {
+                payload: URL_SAFE_NO_PAD.encode("\"tampered\""),
+                ..jws
+            };
+
+            assert_eq!(
+                jws.verify(&key.public_key()).err(),
+                Some(JwsError::InvalidSignature)
+            );
+        }
+    }
+
+    #[test]
+    fn other_key() {
+        let key = signing_key(JwsAlgorithm::Es256);
+        let jws =
+            JsonWebSignature::sign(&protected(JwsAlgorithm::Es256), &"payload", &key).unwrap();
+
+        assert_eq!(
+            jws.verify(&signing_key(JwsAlgorithm::Es256).public_key())
+                .err(),
+            Some(JwsError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn algorithm_key_mismatch() {
+        let key = signing_key(JwsAlgorithm::Es256);
+
+        assert_eq!(
+            JsonWebSignature::sign(&protected(JwsAlgorithm::EdDsa), &"payload", &key).err(),
+            Some(JwsError::IncompatibleKey(JwsAlgorithm::EdDsa))
+        );
+
+        let jws =
+            JsonWebSignature::sign(&protected(JwsAlgorithm::Es256), &"payload", &key).unwrap();
+
+        assert_eq!(
+            jws.verify(&signing_key(JwsAlgorithm::EdDsa).public_key())
+                .err(),
+            Some(JwsError::IncompatibleKey(JwsAlgorithm::Es256))
+        );
+        assert_eq!(
+            jws.verify(&signing_key(JwsAlgorithm::Es384).public_key())
+                .err(),
+            Some(JwsError::IncompatibleKey(JwsAlgorithm::Es256))
+        );
+    }
+
+    #[test]
+    fn padded_base64() {
+        let key = signing_key(JwsAlgorithm::EdDsa);
+        let jws =
+            JsonWebSignature::sign(&protected(JwsAlgorithm::EdDsa), &"payload", &key).unwrap();
+
+        for (padded, member) in [
+            (
+                JsonWebSignature {
+                    signature: format!("{}==", jws.signature),
+                    ..jws.clone()
+                },
+                "signature",
+            ),
+            (
+                JsonWebSignature {
+                    payload: URL_SAFE_NO_PAD.encode("{}") + "=",
+                    ..jws.clone()
+                },
+                "payload",
+            ),
+        ] {
+            assert_eq!(
+                padded.verify(&key.public_key()).err(),
+                Some(JwsError::Base64(member))
+            );
+        }
+    }
+
+    #[test]
+    fn unsupported_algorithm() {
+        assert_eq!(
+            SigningKey::generate_pkcs8(&JwsAlgorithm::Es512).err(),
+            Some(JwsError::UnsupportedAlgorithm(JwsAlgorithm::Es512))
+        );
+        assert_eq!(
+            SigningKey::from_pkcs8(JwsAlgorithm::Hs256, &[]).unwrap_err(),
+            JwsError::UnsupportedAlgorithm(JwsAlgorithm::Hs256)
+        );
+    }
+}
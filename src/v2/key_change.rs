@@ -0,0 +1,352 @@
+use super::{
+    AccountUrl, JsonWebKey, JsonWebSignature, JsonWebSignatureProtected, JwsError, KeyChange,
+    SignedRequest, SigningKey,
+};
+
+impl KeyChange {
+    /// Signs a key change request, producing the outer JSON web signature to be sent to the key
+    /// change URL (`Directory.key_change`)
+    ///
+    /// The inner JSON web signature, over the `KeyChange` object, is signed by the new key and
+    /// the outer JSON web signature is signed by the current (old) key of the ACME account.
+    ///
+    /// For more information, refer to [RFC 8555 § 7.3.5](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3.5)
+    pub fn sign(
+        account: &AccountUrl,
+        url: &str,
+        nonce: impl Into<String>,
+        old_key: &SigningKey,
+        new_key: &SigningKey,
+    ) -> Result<JsonWebSignature, JwsError> {
+        let key_change = KeyChange {
+            account: account.clone(),
+            old_key: old_key.public_key(),
+        };
+        let inner = JsonWebSignature::sign(
+            &JsonWebSignatureProtected {
+                algorithm: new_key.algorithm(),
+                nonce: None,
+                url: url.to_string(),
+                json_web_key: Some(new_key.public_key()),
+                key_id: None,
+            },
+            &key_change,
+            new_key,
+        )?;
+
+        JsonWebSignature::sign(
+            &JsonWebSignatureProtected {
+                algorithm: old_key.algorithm(),
+                nonce: Some(nonce.into()),
+                url: url.to_string(),
+                json_web_key: None,
+                key_id: Some(account.as_str().to_string()),
+            },
+            &inner,
+            old_key,
+        )
+    }
+
+    /// Verifies a key change request against the current key of the ACME account identified by
+    /// the request's `kid`, returning the new key
+    ///
+    /// Both signatures are verified, the inner `url` must match the outer `url`, the inner
+    /// `account` must match the outer `kid` and `oldKey` must match the current key. The ACME
+    /// provider must still check that the new key is not already in use by another account.
+    ///
+    /// For more information, refer to [RFC 8555 § 7.3.5](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3.5)
+    pub fn verify(
+        request: &SignedRequest<JsonWebSignature>,
+        account_key: &JsonWebKey,
+    ) -> Result<JsonWebKey, KeyChangeError> {
+        request.verify(account_key)?;
+
+        let inner = request
+            .payload
+            .as_ref()
+            .ok_or(KeyChangeError::MissingPayload)?;
+        let protected = inner.decode_protected()?;
+
+        let new_key = match (protected.json_web_key, &protected.key_id) {
+            (Some(key), None) => key,
+            _ => return Err(KeyChangeError::InnerKey),
+        };
+
+        if protected.nonce.is_some() {
+            return Err(KeyChangeError::Nonce);
+        }
+
+        let (_, payload) = inner.verify(&new_key)?;
+
+        if protected.url != request.protected.url {
+            return Err(KeyChangeError::UrlMismatch);
+        }
+
+        let key_change: KeyChange =
+            serde_json::from_slice(&payload).map_err(|e| KeyChangeError::Payload(e.to_string()))?;
+
+        if request.protected.key_id.as_deref() != Some(key_change.account.as_str()) {
+            return Err(KeyChangeError::AccountMismatch);
+        }
+
+        if key_change.old_key.to_public() != account_key.to_public() {
+            return Err(KeyChangeError::OldKeyMismatch);
+        }
+
+        Ok(new_key)
+    }
+}
+
+/// Errors encountered while verifying a key change request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyChangeError {
+    /// The outer or inner JSON web signature is invalid
+    Jws(JwsError),
+    /// The outer JSON web signature has an empty payload
+    MissingPayload,
+    /// The inner protected header does not contain a `jwk`, or contains a `kid`
+    InnerKey,
+    /// The inner protected header contains a nonce
+    Nonce,
+    /// The inner `url` does not match the outer `url`
+    UrlMismatch,
+    /// The inner payload is not a valid key change object
+    Payload(String),
+    /// The key change `account` does not match the outer `kid`
+    AccountMismatch,
+    /// The key change `oldKey` does not match the account's current key
+    OldKeyMismatch,
+}
+
+impl KeyChangeError {
+    /// Returns the ACME error type an ACME provider should return for this error
+    pub fn error_type(&self) -> super::ErrorType {
+        match self {
+            KeyChangeError::Jws(error) => error.error_type(),
+            _ => super::ErrorType::Malformed,
+        }
+    }
+}
+
+impl From<JwsError> for KeyChangeError {
+    fn from(error: JwsError) -> KeyChangeError {
+        KeyChangeError::Jws(error)
+    }
+}
+
+impl std::fmt::Display for KeyChangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyChangeError::Jws(error) => write!(f, "key change request is invalid: {}", error),
+            KeyChangeError::MissingPayload => {
+                write!(f, "key change request must contain an inner JWS")
+            }
+            KeyChangeError::InnerKey => write!(
+                f,
+                "key change inner JWS must contain a jwk and must not contain a kid"
+            ),
+            KeyChangeError::Nonce => write!(f, "key change inner JWS must not contain a nonce"),
+            KeyChangeError::UrlMismatch => {
+                write!(f, "key change inner JWS url does not match the request url")
+            }
+            KeyChangeError::Payload(e) => {
+                write!(f, "key change inner JWS payload is invalid: {}", e)
+            }
+            KeyChangeError::AccountMismatch => {
+                write!(f, "key change account does not match the request kid")
+            }
+            KeyChangeError::OldKeyMismatch => {
+                write!(f, "key change oldKey does not match the account key")
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeyChangeError {}
+
+impl From<KeyChangeError> for super::Error {
+    fn from(error: KeyChangeError) -> super::Error {
+        super::Error::new(error.error_type(), error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2::JwsAlgorithm;
+
+    const URL: &str = "https://example.com/acme/key-change";
+
+    fn signing_key() -> SigningKey {
+        let pkcs8 = SigningKey::generate_pkcs8(&JwsAlgorithm::Es256).unwrap();
+        SigningKey::from_pkcs8(JwsAlgorithm::Es256, &pkcs8).unwrap()
+    }
+
+    fn account() -> AccountUrl {
+        AccountUrl::new("https://example.com/acme/acct/1")
+    }
+
+    fn inner_protected(new_key: &SigningKey) -> JsonWebSignatureProtected {
+        JsonWebSignatureProtected {
+            algorithm: new_key.algorithm(),
+            nonce: None,
+            url: URL.to_string(),
+            json_web_key: Some(new_key.public_key()),
+            key_id: None,
+        }
+    }
+
+    /// Signs the inner JWS with the new key and the outer JWS with the old key, as sent to the
+    /// key change URL by `account`
+    fn key_change_request(
+        inner: &JsonWebSignatureProtected,
+        key_change: &KeyChange,
+        old_key: &SigningKey,
+        new_key: &SigningKey,
+    ) -> SignedRequest<JsonWebSignature> {
+        let inner = JsonWebSignature::sign(inner, key_change, new_key).unwrap();
+        let outer = JsonWebSignature::sign(
+            &JsonWebSignatureProtected {
+                algorithm: old_key.algorithm(),
+                nonce: Some("nonce".to_string()),
+                url: URL.to_string(),
+                json_web_key: None,
+                key_id: Some(account().as_str().to_string()),
+            },
+            &inner,
+            old_key,
+        )
+        .unwrap();
+
+        SignedRequest::decode(outer).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let old_key = signing_key();
+        let new_key = signing_key();
+        let signature = KeyChange::sign(&account(), URL, "nonce", &old_key, &new_key).unwrap();
+        let request = SignedRequest::decode(signature).unwrap();
+
+        assert_eq!(
+            KeyChange::verify(&request, &old_key.public_key()),
+            Ok(new_key.public_key())
+        );
+        assert_eq!(
+            KeyChange::verify(&request, &new_key.public_key()),
+            Err(KeyChangeError::Jws(JwsError::InvalidSignature))
+        );
+    }
+
+    #[test]
+    fn mismatches() {
+        let old_key = signing_key();
+        let new_key = signing_key();
+        let key_change = KeyChange {
+            account: account(),
+            old_key: old_key.public_key(),
+        };
+
+        let inner = JsonWebSignatureProtected {
+            url: "https://example.com/acme/new-order".to_string(),
+            ..inner_protected(&new_key)
+        };
+        let request = key_change_request(&inner, &key_change, &old_key, &new_key);
+        assert_eq!(
+            KeyChange::verify(&request, &old_key.public_key()),
+            Err(KeyChangeError::UrlMismatch)
+        );
+
+        let other_account = KeyChange {
+            account: AccountUrl::new("https://example.com/acme/acct/2"),
+            ..key_change.clone()
+        };
+        let request = key_change_request(
+            &inner_protected(&new_key),
+            &other_account,
+            &old_key,
+            &new_key,
+        );
+        assert_eq!(
+            KeyChange::verify(&request, &old_key.public_key()),
+            Err(KeyChangeError::AccountMismatch)
+        );
+
+        let other_old_key = KeyChange {
+            old_key: signing_key().public_key(),
+            ..key_change
+        };
+        let request = key_change_request(
+            &inner_protected(&new_key),
+            &other_old_key,
+            &old_key,
+            &new_key,
+        );
+        let error = KeyChange::verify(&request, &old_key.public_key()).unwrap_err();
+        assert_eq!(error, KeyChangeError::OldKeyMismatch);
+        assert_eq!(error.error_type(), crate::v2::ErrorType::Malformed);
+    }
+
+    #[test]
+    fn inner_protected_header() {
+        let old_key = signing_key();
+        let new_key = signing_key();
+        let key_change = KeyChange {
+            account: account(),
+            old_key: old_key.public_key(),
+        };
+
+        let inner = JsonWebSignatureProtected {
+            nonce: Some("nonce".to_string()),
+            ..inner_protected(&new_key)
+        };
+        let request = key_change_request(&inner, &key_change, &old_key, &new_key);
+        assert_eq!(
+            KeyChange::verify(&request, &old_key.public_key()),
+            Err(KeyChangeError::Nonce)
+        );
+
+        let inner = JsonWebSignatureProtected {
+            key_id: Some(account().as_str().to_string()),
+            ..inner_protected(&new_key)
+        };
+        let request = key_change_request(&inner, &key_change, &old_key, &new_key);
+        assert_eq!(
+            KeyChange::verify(&request, &old_key.public_key()),
+            Err(KeyChangeError::InnerKey)
+        );
+
+        let inner = JsonWebSignatureProtected {
+            json_web_key: None,
+            key_id: Some(account().as_str().to_string()),
+            ..inner_protected(&new_key)
+        };
+        let request = key_change_request(&inner, &key_change, &old_key, &new_key);
+        assert_eq!(
+            KeyChange::verify(&request, &old_key.public_key()),
+            Err(KeyChangeError::InnerKey)
+        );
+    }
+
+    #[test]
+    fn bad_inner_signature() {
+        let old_key = signing_key();
+        let new_key = signing_key();
+        let key_change = KeyChange {
+            account: account(),
+            old_key: old_key.public_key(),
+        };
+
+        // The inner JWS claims the new key but is signed by another key
+        let request = key_change_request(
+            &inner_protected(&new_key),
+            &key_change,
+            &old_key,
+            &signing_key(),
+        );
+        assert_eq!(
+            KeyChange::verify(&request, &old_key.public_key()),
+            Err(KeyChangeError::Jws(JwsError::InvalidSignature))
+        );
+    }
+}
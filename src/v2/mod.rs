@@ -1,15 +1,65 @@
 mod account;
 mod authorization;
+#[cfg(feature = "base64")]
+mod certificate;
+#[cfg(all(feature = "crypto", feature = "json"))]
+mod client;
+mod contact;
+#[cfg(feature = "base64")]
+mod der;
 mod directory;
 mod error;
+#[cfg(all(feature = "crypto", feature = "json"))]
+mod external_account;
+mod headers;
 mod identifier;
 mod jose;
+#[cfg(all(feature = "crypto", feature = "json"))]
+mod jws;
+#[cfg(all(feature = "crypto", feature = "json"))]
+mod key_change;
+#[cfg(feature = "crypto")]
+mod nonce;
 mod order;
+#[cfg(all(feature = "crypto", feature = "json", feature = "time"))]
+mod provider;
+mod renewal_info;
+mod resource_url;
+#[cfg(feature = "json")]
+mod signed_request;
+mod status;
+#[cfg(all(feature = "crypto", feature = "json", feature = "time"))]
+mod store;
+mod timestamp;
 
 pub use account::*;
 pub use authorization::*;
+#[cfg(feature = "base64")]
+pub use certificate::*;
+#[cfg(all(feature = "crypto", feature = "json"))]
+pub use client::*;
+pub use contact::*;
 pub use directory::*;
 pub use error::*;
+#[cfg(all(feature = "crypto", feature = "json"))]
+pub use external_account::*;
+pub use headers::*;
 pub use identifier::*;
 pub use jose::*;
+#[cfg(all(feature = "crypto", feature = "json"))]
+pub use jws::*;
+#[cfg(all(feature = "crypto", feature = "json"))]
+pub use key_change::*;
+#[cfg(feature = "crypto")]
+pub use nonce::*;
 pub use order::*;
+#[cfg(all(feature = "crypto", feature = "json", feature = "time"))]
+pub use provider::*;
+pub use renewal_info::*;
+pub use resource_url::*;
+#[cfg(feature = "json")]
+pub use signed_request::*;
+pub use status::*;
+#[cfg(all(feature = "crypto", feature = "json", feature = "time"))]
+pub use store::*;
+pub use timestamp::*;
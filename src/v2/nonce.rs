@@ -0,0 +1,473 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::{
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+
+/// Defines a bounded set of nonces, each of which expires after the same lifetime
+///
+/// Removed nonces are left in the insertion order and skipped when they reach its front, so that
+/// removing a nonce does not have to search the order.
+#[derive(Debug)]
+struct NonceSet {
+    capacity: usize,
+    lifetime: Duration,
+    nonces: HashMap<String, Instant>,
+    order: VecDeque<(String, Instant)>,
+}
+
+impl NonceSet {
+    fn new(capacity: usize, lifetime: Duration) -> NonceSet {
+        NonceSet {
+            capacity,
+            lifetime,
+            nonces: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Returns whether an entry of the order is still the nonce held by the set, rather than one
+    /// that was removed (and possibly inserted again since)
+    fn is_live(&self, nonce: &str, inserted: Instant) -> bool {
+        self.nonces.get(nonce) == Some(&inserted)
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while let Some((nonce, inserted)) = self.order.front() {
+            if self.is_live(nonce, *inserted) {
+                if now.duration_since(*inserted) < self.lifetime {
+                    break;
+                }
+
+                self.nonces.remove(nonce);
+            }
+
+            self.order.pop_front();
+        }
+
+        // Removed nonces stuck behind live ones are dropped once they could fill the set twice
+        if self.order.len() > 2 * self.capacity.max(1) {
+            let nonces = &self.nonces;
+            self.order
+                .retain(|(nonce, inserted)| nonces.get(nonce) == Some(inserted));
+        }
+    }
+
+    fn push(&mut self, nonce: String, now: Instant) {
+        self.nonces.insert(nonce.clone(), now);
+        self.order.push_back((nonce, now));
+    }
+
+    fn insert(&mut self, nonce: String, now: Instant) {
+        self.expire(now);
+
+        while self.nonces.len() >= self.capacity.max(1) {
+            if let Some((oldest, inserted)) = self.order.pop_front() {
+                if self.is_live(&oldest, inserted) {
+                    self.nonces.remove(&oldest);
+                }
+            }
+        }
+
+        self.push(nonce, now);
+    }
+
+    /// Inserts a nonce unless the set is full of unexpired nonces, returning false if it is
+    fn try_insert(&mut self, nonce: String, now: Instant) -> bool {
+        self.expire(now);
+
+        if self.nonces.len() >= self.capacity.max(1) {
+            return false;
+        }
+
+        self.push(nonce, now);
+        true
+    }
+
+    fn contains(&mut self, nonce: &str, now: Instant) -> bool {
+        self.expire(now);
+        self.nonces.contains_key(nonce)
+    }
+
+    fn remove(&mut self, nonce: &str, now: Instant) -> bool {
+        self.expire(now);
+        self.nonces.remove(nonce).is_some()
+    }
+}
+
+#[derive(Debug)]
+enum NonceMode {
+    Stateful(std::sync::Mutex<NonceSet>),
+    Stateless {
+        key: hmac::Key,
+        consumed: std::sync::Mutex<NonceSet>,
+    },
+}
+
+/// Defines an ACME provider nonce service, which issues unpredictable base64url nonces and
+/// consumes each of them once.
+///
+/// In the default (stateful) mode, issued nonces are remembered in a bounded set and expire after
+/// their lifetime; when the set is full, the oldest nonces are forgotten. In the stateless mode,
+/// each nonce carries its issue time and an HMAC, so that any instance sharing the key can check
+/// it without shared storage; consumed nonces are only remembered by the instance which consumed
+/// them, until they expire. As forgetting an unexpired consumed nonce would allow it to be
+/// replayed, nonces are refused once the set of consumed nonces is full.
+///
+/// For more information, refer to [RFC 8555 § 6.5](https://datatracker.ietf.org/doc/html/rfc8555#section-6.5)
+#[derive(Debug)]
+pub struct NonceService {
+    mode: NonceMode,
+    lifetime: Duration,
+    random: SystemRandom,
+}
+
+const NONCE_RANDOM_LEN: usize = 16;
+const NONCE_MAC_LEN: usize = 32;
+
+impl NonceService {
+    /// Creates a stateful nonce service remembering up to `capacity` nonces for `lifetime`
+    pub fn new(capacity: usize, lifetime: Duration) -> NonceService {
+        NonceService {
+            mode: NonceMode::Stateful(std::sync::Mutex::new(NonceSet::new(capacity, lifetime))),
+            lifetime,
+            random: SystemRandom::new(),
+        }
+    }
+
+    /// Creates a stateless nonce service authenticating nonces with HMAC-SHA256 using the key,
+    /// remembering up to `capacity` consumed nonces
+    ///
+    /// The capacity should exceed the number of requests the instance receives during a nonce
+    /// lifetime, as further requests are refused until consumed nonces expire.
+    pub fn stateless(key: &[u8], capacity: usize, lifetime: Duration) -> NonceService {
+        NonceService {
+            mode: NonceMode::Stateless {
+                key: hmac::Key::new(hmac::HMAC_SHA256, key),
+                consumed: std::sync::Mutex::new(NonceSet::new(capacity, lifetime)),
+            },
+            lifetime,
+            random: SystemRandom::new(),
+        }
+    }
+
+    /// Issues a nonce, to be returned in a `Replay-Nonce` header field
+    pub fn issue(&self) -> Result<String, NonceError> {
+        let mut random = [0; NONCE_RANDOM_LEN];
+        self.random
+            .fill(&mut random)
+            .map_err(|_| NonceError::Random)?;
+
+        match &self.mode {
+            NonceMode::Stateful(issued) => {
+                let nonce = URL_SAFE_NO_PAD.encode(random);
+                lock(issued).insert(nonce.clone(), Instant::now());
+                Ok(nonce)
+            }
+            NonceMode::Stateless { key, .. } => {
+                let mut nonce = unix_time().to_be_bytes().to_vec();
+                nonce.extend_from_slice(&random);
+                let tag = hmac::sign(key, &nonce);
+                nonce.extend_from_slice(tag.as_ref());
+                Ok(URL_SAFE_NO_PAD.encode(nonce))
+            }
+        }
+    }
+
+    /// Consumes a nonce, which must have been issued by this service, must not have expired and
+    /// must not have been consumed before
+    pub fn consume(&self, nonce: &str) -> Result<(), NonceError> {
+        let decoded = URL_SAFE_NO_PAD
+            .decode(nonce)
+            .map_err(|_| NonceError::Malformed)?;
+        let now = Instant::now();
+
+        match &self.mode {
+            NonceMode::Stateful(issued) => match lock(issued).remove(nonce, now) {
+                true => Ok(()),
+                false => Err(NonceError::Unknown),
+            },
+            NonceMode::Stateless { key, consumed } => {
+                if decoded.len() != 8 + NONCE_RANDOM_LEN + NONCE_MAC_LEN {
+                    return Err(NonceError::Malformed);
+                }
+
+                let (message, tag) = decoded.split_at(8 + NONCE_RANDOM_LEN);
+                hmac::verify(key, message, tag).map_err(|_| NonceError::Unknown)?;
+
+                let mut issued = [0; 8];
+                issued.copy_from_slice(&message[..8]);
+                let age = unix_time().saturating_sub(u64::from_be_bytes(issued));
+                if age >= self.lifetime.as_secs() {
+                    return Err(NonceError::Unknown);
+                }
+
+                let mut consumed = lock(consumed);
+                if consumed.contains(nonce, now) {
+                    return Err(NonceError::Unknown);
+                }
+
+                match consumed.try_insert(nonce.to_string(), now) {
+                    true => Ok(()),
+                    false => Err(NonceError::Exhausted),
+                }
+            }
+        }
+    }
+
+    /// Consumes the nonce of a request's protected header
+    pub fn consume_request(
+        &self,
+        protected: &super::JsonWebSignatureProtected,
+    ) -> Result<(), NonceError> {
+        self.consume(protected.nonce.as_deref().ok_or(NonceError::Missing)?)
+    }
+}
+
+fn lock<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Errors encountered while issuing or consuming a nonce
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NonceError {
+    /// The request does not contain a nonce
+    Missing,
+    /// The nonce is not valid base64url or has the wrong length
+    Malformed,
+    /// The nonce was not issued by the ACME provider, has expired or has already been used
+    Unknown,
+    /// The stateless nonce service cannot remember any more consumed nonces
+    Exhausted,
+    /// A nonce could not be generated
+    Random,
+}
+
+impl NonceError {
+    /// Returns the ACME error type an ACME provider should return for this error
+    pub fn error_type(&self) -> super::ErrorType {
+        match self {
+            NonceError::Exhausted | NonceError::Random => super::ErrorType::ServerInternal,
+            _ => super::ErrorType::BadNonce,
+        }
+    }
+}
+
+impl std::fmt::Display for NonceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NonceError::Missing => write!(f, "JWS protected header must contain a nonce"),
+            NonceError::Malformed => write!(f, "nonce is malformed"),
+            NonceError::Unknown => write!(f, "nonce is invalid, expired or already used"),
+            NonceError::Exhausted => write!(f, "too many nonces have been consumed"),
+            NonceError::Random => write!(f, "nonce could not be generated"),
+        }
+    }
+}
+
+impl std::error::Error for NonceError {}
+
+impl From<NonceError> for super::Error {
+    fn from(error: NonceError) -> super::Error {
+        super::Error::new(error.error_type(), error.to_string())
+    }
+}
+
+/// Defines an ACME client nonce pool, which collects the nonces received in `Replay-Nonce` header
+/// fields and hands out the most recent one first.
+///
+/// A request which fails with a `badNonce` error is retried once with a fresh nonce.
+///
+/// For more information, refer to [RFC 8555 § 6.5](https://datatracker.ietf.org/doc/html/rfc8555#section-6.5)
+#[derive(Clone, Debug)]
+pub struct NoncePool {
+    capacity: usize,
+    nonces: VecDeque<String>,
+    retried: bool,
+}
+
+impl NoncePool {
+    /// Creates an empty pool holding up to `capacity` nonces
+    pub fn new(capacity: usize) -> NoncePool {
+        NoncePool {
+            capacity: capacity.max(1),
+            nonces: VecDeque::new(),
+            retried: false,
+        }
+    }
+
+    /// Adds a nonce received in a `Replay-Nonce` header field, forgetting the oldest nonce if the
+    /// pool is full
+    pub fn push(&mut self, nonce: impl Into<String>) {
+        if self.nonces.len() >= self.capacity {
+            self.nonces.pop_front();
+        }
+
+        self.nonces.push_back(nonce.into());
+    }
+
+    /// Takes the most recent nonce, or returns `None` if a nonce must be fetched from the new
+    /// nonce URL
+    pub fn pop(&mut self) -> Option<String> {
+        self.nonces.pop_back()
+    }
+
+    /// Returns true if the pool holds no nonce
+    pub fn is_empty(&self) -> bool {
+        self.nonces.is_empty()
+    }
+
+    /// Returns the number of nonces in the pool
+    pub fn len(&self) -> usize {
+        self.nonces.len()
+    }
+
+    /// Returns true if a request which failed with the error should be retried, i.e. if the error
+    /// is the first `badNonce` error since the last successful request
+    pub fn retry(&mut self, error: &super::Error) -> bool {
+        if error.type_ != super::ErrorType::BadNonce || self.retried {
+            return false;
+        }
+
+        self.retried = true;
+        true
+    }
+
+    /// Records that a request succeeded, allowing the next `badNonce` error to be retried
+    pub fn succeeded(&mut self) {
+        self.retried = false;
+    }
+}
+
+impl Default for NoncePool {
+    fn default() -> NoncePool {
+        NoncePool::new(8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIFETIME: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn stateful() {
+        let service = NonceService::new(2, LIFETIME);
+        let a = service.issue().unwrap();
+        let b = service.issue().unwrap();
+        let c = service.issue().unwrap();
+
+        assert_eq!(service.consume(&a), Err(NonceError::Unknown));
+        assert_eq!(service.consume(&b), Ok(()));
+        assert_eq!(service.consume(&b), Err(NonceError::Unknown));
+        assert_eq!(service.consume(&c), Ok(()));
+        assert_eq!(service.consume("not base64!"), Err(NonceError::Malformed));
+    }
+
+    #[test]
+    fn stateless() {
+        let service = NonceService::stateless(b"key", 10, LIFETIME);
+        let other = NonceService::stateless(b"key", 10, LIFETIME);
+        let nonce = service.issue().unwrap();
+
+        assert_eq!(other.consume(&nonce), Ok(()));
+        assert_eq!(other.consume(&nonce), Err(NonceError::Unknown));
+        assert_eq!(
+            NonceService::stateless(b"other key", 10, LIFETIME).consume(&service.issue().unwrap()),
+            Err(NonceError::Unknown)
+        );
+        assert_eq!(
+            service.consume(&URL_SAFE_NO_PAD.encode([0; 16])),
+            Err(NonceError::Malformed)
+        );
+    }
+
+    #[test]
+    fn stateless_replay_when_full() {
+        let service = NonceService::stateless(b"key", 1, LIFETIME);
+        let a = service.issue().unwrap();
+        let b = service.issue().unwrap();
+
+        assert_eq!(service.consume(&a), Ok(()));
+        assert_eq!(service.consume(&b), Err(NonceError::Exhausted));
+        assert_eq!(service.consume(&a), Err(NonceError::Unknown));
+        assert_eq!(
+            NonceError::Exhausted.error_type(),
+            crate::v2::ErrorType::ServerInternal
+        );
+    }
+
+    #[test]
+    fn consumed_nonces_expire() {
+        let now = Instant::now();
+        let mut set = NonceSet::new(1, LIFETIME);
+
+        assert!(set.try_insert("a".to_string(), now));
+        assert!(!set.try_insert("b".to_string(), now + LIFETIME / 2));
+        assert!(set.contains("a", now + LIFETIME / 2));
+        assert!(set.try_insert("b".to_string(), now + LIFETIME));
+        assert!(!set.contains("a", now + LIFETIME));
+    }
+
+    #[test]
+    fn removed_nonces() {
+        let now = Instant::now();
+        let mut set = NonceSet::new(2, LIFETIME);
+
+        set.insert("a".to_string(), now);
+        set.insert("b".to_string(), now);
+        assert!(set.remove("b", now));
+        assert!(!set.remove("b", now));
+
+        // The removed nonce no longer counts towards the capacity, and inserting it again is not
+        // undone by its stale entry
+        set.insert("b".to_string(), now + LIFETIME / 2);
+        assert!(set.contains("a", now + LIFETIME / 2));
+        assert!(set.contains("b", now + LIFETIME));
+        assert!(!set.contains("a", now + LIFETIME));
+
+        for i in 0..100 {
+            let nonce = i.to_string();
+            set.insert(nonce.clone(), now + LIFETIME);
+            assert!(set.remove(&nonce, now + LIFETIME));
+        }
+
+        assert!(set.contains("b", now + LIFETIME));
+        assert!(set.order.len() <= 4);
+    }
+
+    #[test]
+    fn pool() {
+        let mut pool = NoncePool::new(2);
+        pool.push("a");
+        pool.push("b");
+        pool.push("c");
+
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.pop().as_deref(), Some("c"));
+        assert_eq!(pool.pop().as_deref(), Some("b"));
+        assert!(pool.is_empty());
+
+        let bad_nonce = crate::v2::Error::new(crate::v2::ErrorType::BadNonce, "bad nonce");
+
+        assert!(pool.retry(&bad_nonce));
+        assert!(!pool.retry(&bad_nonce));
+        pool.succeeded();
+        assert!(pool.retry(&bad_nonce));
+        assert!(!pool.retry(&crate::v2::Error::new(
+            crate::v2::ErrorType::Malformed,
+            "malformed"
+        )));
+    }
+}
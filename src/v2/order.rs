@@ -12,11 +12,16 @@ pub struct NewOrder {
     /// Requested value for certificate's notBefore value
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
     #[cfg_attr(feature = "json", serde(rename = "notBefore"))]
-    pub not_before: Option<String>,
+    pub not_before: Option<super::Timestamp>,
     /// Requested value for certificate's notAfter value
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
     #[cfg_attr(feature = "json", serde(rename = "notAfter"))]
-    pub not_after: Option<String>,
+    pub not_after: Option<super::Timestamp>,
+    /// Renewal information certificate identifier of the certificate this order replaces
+    ///
+    /// For more information, refer to [RFC 9773 § 5](https://datatracker.ietf.org/doc/html/rfc9773#section-5)
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub replaces: Option<String>,
 }
 
 #[cfg(feature = "json")]
@@ -40,26 +45,29 @@ impl NewOrder {
 pub struct Order {
     /// Order status
     pub status: OrderStatus,
-    /// Order expiration time
-    pub expires: String,
+    /// Order expiration time, required for pending and valid orders
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub expires: Option<super::Timestamp>,
     /// Array of requested identifiers
     pub identifiers: Vec<super::Identifier>,
     /// Requested value for certificate's notBefore value
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
     #[cfg_attr(feature = "json", serde(rename = "notBefore"))]
-    pub not_before: Option<String>,
+    pub not_before: Option<super::Timestamp>,
     /// Requested value for certificate's notAfter value
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
     #[cfg_attr(feature = "json", serde(rename = "notAfter"))]
-    pub not_after: Option<String>,
+    pub not_after: Option<super::Timestamp>,
     /// Error encountered during domain validation, certificate issuance, etc.
-    pub error: Option<String>,
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub error: Option<super::Error>,
     /// Authorizations which need to be completed in order to finalize the order
-    pub authorizations: Option<Vec<String>>,
+    pub authorizations: Vec<super::AuthorizationUrl>,
     /// URL to finalize order
-    pub finalize: String,
-    /// URL to retrieve certificate issued by ACME provider
-    pub certificate: String,
+    pub finalize: super::FinalizeUrl,
+    /// URL to retrieve certificate issued by ACME provider, present once the order is valid
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    pub certificate: Option<super::CertificateUrl>,
 }
 
 #[cfg(feature = "json")]
@@ -75,6 +83,29 @@ impl Order {
     }
 }
 
+#[cfg(feature = "time")]
+impl Order {
+    /// Returns the expiration time, or `None` if the order has none or it is not a valid RFC 3339
+    /// date-time
+    pub fn expires_at(&self) -> Option<time::OffsetDateTime> {
+        self.expires
+            .as_ref()
+            .and_then(super::Timestamp::to_date_time)
+    }
+
+    /// Returns true if the order has an expiration time and it has passed as of `now`
+    pub fn is_expired(&self, now: time::OffsetDateTime) -> bool {
+        self.expires_at().is_some_and(|expires| expires <= now)
+    }
+
+    /// Returns the time remaining until the order expires, which is negative once it has expired
+    ///
+    /// Returns `None` if the order has no expiration time.
+    pub fn expires_in(&self, now: time::OffsetDateTime) -> Option<time::Duration> {
+        self.expires_at().map(|expires| expires - now)
+    }
+}
+
 /// Defines an ACME order finalize object
 ///
 /// For more information, refer to [RFC 8555 § 7.4](https://datatracker.ietf.org/doc/html/rfc8555#section-7.4)
@@ -102,7 +133,7 @@ impl OrderFinalize {
 /// Order resource status values
 ///
 /// For more information, refer to [RFC 8555 § 7.1.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.6)
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum OrderStatus {
     #[cfg_attr(feature = "json", serde(rename = "pending"))]
@@ -117,6 +148,76 @@ pub enum OrderStatus {
     Invalid,
 }
 
+impl OrderStatus {
+    /// Returns the order status after the event, or an error if the RFC 8555 state diagram does
+    /// not allow the event in the current status
+    ///
+    /// For more information, refer to [RFC 8555 § 7.1.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.6)
+    pub fn transition(
+        self,
+        event: OrderEvent,
+    ) -> Result<OrderStatus, super::StatusTransitionError> {
+        match (self, event) {
+            (OrderStatus::Pending, OrderEvent::AuthorizationsValid) => Ok(OrderStatus::Ready),
+            (OrderStatus::Ready, OrderEvent::Finalize) => Ok(OrderStatus::Processing),
+            (OrderStatus::Processing, OrderEvent::CertificateIssued) => Ok(OrderStatus::Valid),
+            (
+                OrderStatus::Pending | OrderStatus::Ready | OrderStatus::Processing,
+                OrderEvent::Fail,
+            ) => Ok(OrderStatus::Invalid),
+            (status, event) => Err(super::StatusTransitionError::Order(status, event)),
+        }
+    }
+
+    /// Returns true if the order has been processed, i.e. its status is `valid` or `invalid`
+    pub fn is_final(&self) -> bool {
+        self.is_terminal()
+    }
+
+    /// Returns true if no event can change the order status
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, OrderStatus::Valid | OrderStatus::Invalid)
+    }
+
+    /// Returns the status value used in the order resource
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Pending => "pending",
+            OrderStatus::Ready => "ready",
+            OrderStatus::Processing => "processing",
+            OrderStatus::Valid => "valid",
+            OrderStatus::Invalid => "invalid",
+        }
+    }
+}
+
+/// Order resource status transition events
+///
+/// For more information, refer to [RFC 8555 § 7.1.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.6)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OrderEvent {
+    /// All of the order's authorizations became `valid` (`pending` to `ready`)
+    AuthorizationsValid,
+    /// The ACME provider received a finalize request (`ready` to `processing`)
+    Finalize,
+    /// The ACME provider issued the certificate (`processing` to `valid`)
+    CertificateIssued,
+    /// An authorization failed or an error occurred (to `invalid`)
+    Fail,
+}
+
+impl OrderEvent {
+    /// Returns a short description of the event
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderEvent::AuthorizationsValid => "authorizations valid",
+            OrderEvent::Finalize => "finalize",
+            OrderEvent::CertificateIssued => "certificate issued",
+            OrderEvent::Fail => "fail",
+        }
+    }
+}
+
 /// Defines a certificate revocation request
 ///
 /// For more information, refer to [RFC 8555 § 7.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.6)
@@ -128,6 +229,7 @@ pub struct CertificateRevocation {
     /// Reason for certificate revocation
     ///
     /// For more information, refer to [RFC 5280 § 5.3.1](https://datatracker.ietf.org/doc/html/rfc5280#section-5.3.1)
+    #[cfg_attr(feature = "json", serde(default))]
     #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
     #[cfg_attr(
         feature = "json",
@@ -155,8 +257,11 @@ impl CertificateRevocation {
 
 /// Certificate revocation reason values
 ///
+/// Reason codes are serialized as JSON integers; codes not assigned by RFC 5280 (i.e. 7 and
+/// anything outside 0 to 10) are rejected.
+///
 /// For more information, refer to [RFC 5280 § 5.3.1](https://datatracker.ietf.org/doc/html/rfc5280#section-5.3.1)
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub enum CertificateRevocationReason {
     Unspecified,
@@ -169,7 +274,6 @@ pub enum CertificateRevocationReason {
     RemoveFromCertificateRevocationList,
     PrivilegeWithdrawn,
     AuthorityAttributeCompromise,
-    Other(i32),
 }
 
 #[cfg(feature = "json")]
@@ -181,7 +285,10 @@ where
 {
     use self::CertificateRevocationReason::*;
 
-    let n = String::deserialize(deserializer)?.parse::<i32>().unwrap();
+    let n = match Option::<i32>::deserialize(deserializer)? {
+        Some(n) => n,
+        None => return Ok(None),
+    };
 
     Ok(Some(match n {
         0 => Unspecified,
@@ -194,7 +301,12 @@ where
         8 => RemoveFromCertificateRevocationList,
         9 => PrivilegeWithdrawn,
         10 => AuthorityAttributeCompromise,
-        _ => Other(n),
+        _ => {
+            return Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Signed(n.into()),
+                &"a revocation reason code (0 to 6 or 8 to 10)",
+            ))
+        }
     }))
 }
 
@@ -208,8 +320,12 @@ where
 {
     use self::CertificateRevocationReason::*;
 
-    serializer.serialize_i32(match type_.clone().unwrap() {
-        Other(i) => i,
+    let reason = match type_ {
+        Some(reason) => reason,
+        None => return serializer.serialize_none(),
+    };
+
+    serializer.serialize_i32(match *reason {
         Unspecified => 0,
         KeyCompromise => 1,
         CertificateAuthorityCompromise => 2,
@@ -222,3 +338,206 @@ where
         AuthorityAttributeCompromise => 10,
     })
 }
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revocation_reason_codes() {
+        for (code, reason) in [
+            (0, CertificateRevocationReason::Unspecified),
+            (1, CertificateRevocationReason::KeyCompromise),
+            (
+                2,
+                CertificateRevocationReason::CertificateAuthorityCompromise,
+            ),
+            (3, CertificateRevocationReason::AffiliationChanged),
+            (4, CertificateRevocationReason::Superseded),
+            (5, CertificateRevocationReason::CessationOfOperation),
+            (6, CertificateRevocationReason::CertificateHold),
+            (
+                8,
+                CertificateRevocationReason::RemoveFromCertificateRevocationList,
+            ),
+            (9, CertificateRevocationReason::PrivilegeWithdrawn),
+            (
+                10,
+                CertificateRevocationReason::AuthorityAttributeCompromise,
+            ),
+        ] {
+            let json = format!(r#"{{"certificate":"MIIB","reason":{}}}"#, code);
+            let revocation = CertificateRevocation::from_str(&json).unwrap();
+
+            assert_eq!(revocation.reason, Some(reason));
+            assert_eq!(revocation.to_string().unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn revocation_reason_null_or_missing() {
+        for json in [
+            r#"{"certificate":"MIIB","reason":null}"#,
+            r#"{"certificate":"MIIB"}"#,
+        ] {
+            let revocation = CertificateRevocation::from_str(json).unwrap();
+
+            assert_eq!(revocation.reason, None);
+            assert_eq!(revocation.to_string().unwrap(), r#"{"certificate":"MIIB"}"#);
+        }
+    }
+
+    #[test]
+    fn revocation_reason_invalid() {
+        for reason in ["7", "11", "99", "-1", "\"1\"", "1.5", "4294967296"] {
+            let json = format!(r#"{{"certificate":"MIIB","reason":{}}}"#, reason);
+
+            assert!(
+                CertificateRevocation::from_str(&json).is_err(),
+                "{}",
+                reason
+            );
+        }
+    }
+
+    #[test]
+    fn order_without_expires() {
+        let order = Order::from_str(
+            r#"{
+                "status": "invalid",
+                "identifiers": [{"type": "dns", "value": "example.com"}],
+                "authorizations": ["https://example.com/acme/authz/PAniVnsZcis"],
+                "finalize": "https://example.com/acme/order/TOlocE8rfgo/finalize"
+            }"#,
+        )
+        .unwrap();
+
+        assert!(order.expires.is_none());
+        assert!(!order.to_string().unwrap().contains("expires"));
+
+        #[cfg(feature = "time")]
+        {
+            let now = time::OffsetDateTime::now_utc();
+            assert!(!order.is_expired(now));
+            assert_eq!(order.expires_at(), None);
+            assert_eq!(order.expires_in(now), None);
+        }
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn order_expiry() {
+        let order = Order::from_str(
+            r#"{
+                "status": "pending",
+                "expires": "2016-01-05T14:09:07.99Z",
+                "identifiers": [{"type": "dns", "value": "example.com"}],
+                "authorizations": ["https://example.com/acme/authz/PAniVnsZcis"],
+                "finalize": "https://example.com/acme/order/TOlocE8rfgo/finalize"
+            }"#,
+        )
+        .unwrap();
+        let expires = order.expires_at().unwrap();
+
+        assert!(!order.is_expired(expires - time::Duration::seconds(1)));
+        assert!(order.is_expired(expires));
+        assert_eq!(
+            order.expires_in(expires - time::Duration::hours(1)),
+            Some(time::Duration::hours(1))
+        );
+    }
+
+    fn order_fixture(status: &str, extra: &str) -> String {
+        format!(
+            r#"{{
+                "status": "{}",
+                "expires": "2016-01-05T14:09:07.99Z",
+                "identifiers": [
+                    {{"type": "dns", "value": "www.example.org"}},
+                    {{"type": "dns", "value": "example.org"}}
+                ],
+                "notBefore": "2016-01-01T00:00:00Z",
+                "notAfter": "2016-01-08T00:00:00Z",
+                "authorizations": [
+                    "https://example.com/acme/authz/PAniVnsZcis",
+                    "https://example.com/acme/authz/r4HqLzrSrpI"
+                ],
+                "finalize": "https://example.com/acme/order/TOlocE8rfgo/finalize"{}
+            }}"#,
+            status, extra
+        )
+    }
+
+    #[test]
+    fn order_statuses() {
+        for (status, expected) in [
+            ("pending", OrderStatus::Pending),
+            ("ready", OrderStatus::Ready),
+            ("processing", OrderStatus::Processing),
+            ("valid", OrderStatus::Valid),
+            ("invalid", OrderStatus::Invalid),
+        ] {
+            let order = Order::from_str(&order_fixture(status, "")).unwrap();
+
+            assert_eq!(order.status, expected);
+            assert_eq!(order.status.as_str(), status);
+            assert_eq!(order.identifiers.len(), 2);
+            assert_eq!(order.authorizations.len(), 2);
+            assert!(order.error.is_none());
+            assert!(order.certificate.is_none());
+
+            let order = Order::from_str(&order.to_string().unwrap()).unwrap();
+            assert_eq!(order.status, expected);
+        }
+    }
+
+    #[test]
+    fn invalid_order_with_error() {
+        let order = Order::from_str(&order_fixture(
+            "invalid",
+            r#",
+                "error": {
+                    "type": "urn:ietf:params:acme:error:compound",
+                    "detail": "Some of the identifiers could not be validated",
+                    "subproblems": [
+                        {
+                            "type": "urn:ietf:params:acme:error:caa",
+                            "detail": "CAA record forbids issuance",
+                            "identifier": {"type": "dns", "value": "www.example.org"}
+                        }
+                    ]
+                }"#,
+        ))
+        .unwrap();
+        let error = order.error.unwrap();
+        let sub_problems = error.sub_problems.unwrap();
+
+        assert_eq!(order.status, OrderStatus::Invalid);
+        assert_eq!(error.type_, super::super::ErrorType::Compound);
+        assert_eq!(sub_problems.len(), 1);
+        assert_eq!(
+            sub_problems[0].type_,
+            super::super::ErrorType::CertificationAuthorityAuthorization
+        );
+        assert_eq!(
+            sub_problems[0].identifier.as_ref().unwrap().value,
+            "www.example.org"
+        );
+    }
+
+    #[test]
+    fn valid_order_with_certificate() {
+        let order = Order::from_str(&order_fixture(
+            "valid",
+            r#",
+                "certificate": "https://example.com/acme/cert/mAt3xBGaobw""#,
+        ))
+        .unwrap();
+
+        assert_eq!(order.status, OrderStatus::Valid);
+        assert_eq!(
+            order.certificate.unwrap().as_str(),
+            "https://example.com/acme/cert/mAt3xBGaobw"
+        );
+    }
+}
@@ -0,0 +1,1777 @@
+use std::num::NonZeroUsize;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::Value;
+use time::{Duration, OffsetDateTime};
+
+use super::{
+    Account, AccountEvent, AccountOrders, AccountOrdersPage, AccountOrdersUrl, AccountRecord,
+    AccountStatus, AccountUpdate, AccountUrl, Authorization, AuthorizationEvent,
+    AuthorizationRecord, AuthorizationStatus, AuthorizationUpdate, CertificateChain,
+    CertificateRecord, CertificateRevocation, CertificateRevocationReason, CertificateUrl,
+    Challenge, ChallengeEvent, ChallengeRecord, ChallengeStatus, ChallengeType, ChallengeUrl,
+    Directory, DirectoryMetadata, ErrorType, ExternalAccountBinding, Identifier, JsonWebKey,
+    JsonWebSignature, JwsAlgorithm, KeyChange, KeyChangeUrl, NewAccount, NewAccountUrl,
+    NewNonceUrl, NewOrder, NewOrderUrl, NonceService, Order, OrderEvent, OrderFinalize,
+    OrderRecord, OrderStatus, OrderUrl, ProviderStore, RevokeCertificateUrl, SignedRequest,
+    Timestamp,
+};
+
+type ProviderResult<T> = Result<T, Box<super::Error>>;
+
+/// Defines the resources served by an [`AcmeProvider`], as resolved from request URLs
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ProviderResource {
+    Directory,
+    NewNonce,
+    NewAccount,
+    NewOrder,
+    RevokeCertificate,
+    KeyChange,
+    Account(String),
+    AccountOrders(String, usize),
+    Order(String),
+    Finalize(String),
+    Authorization(String),
+    Challenge(String),
+    Certificate(String),
+}
+
+/// Defines an ACME provider, which implements the RFC 8555 account, order, authorization,
+/// challenge and certificate operations on top of a [`ProviderStore`].
+///
+/// Each operation takes a decoded request, checks its URL, algorithm and signature, and returns
+/// the resulting resource or an ACME error to be returned to the ACME client. Errors for unknown
+/// resources (e.g. an unknown order identifier) have their `status` set to 404, the HTTP status
+/// code to return them with. Nonces are issued
+/// by `new_nonce` and each request's nonce is consumed by the provider's [`NonceService`].
+/// Challenge validation and certificate issuance are performed by the caller, which reports their
+/// results using `complete_challenge`, `retry_challenge`, `issue_certificate` and `fail_order`.
+///
+/// For more information, refer to [RFC 8555 § 7](https://datatracker.ietf.org/doc/html/rfc8555#section-7)
+#[derive(Debug)]
+pub struct AcmeProvider<S> {
+    base_url: String,
+    store: S,
+    metadata: Option<DirectoryMetadata>,
+    external_account_keys: Vec<ExternalAccountBinding>,
+    challenge_types: Vec<ChallengeType>,
+    algorithms: Vec<JwsAlgorithm>,
+    order_lifetime: Duration,
+    authorization_lifetime: Duration,
+    orders_page_size: NonZeroUsize,
+    nonces: NonceService,
+    random: SystemRandom,
+}
+
+impl<S: ProviderStore> AcmeProvider<S> {
+    /// Creates a provider serving its resources under the base URL (e.g.
+    /// `https://acme.example.com/acme`)
+    pub fn new(base_url: impl Into<String>, store: S) -> AcmeProvider<S> {
+        AcmeProvider {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            store,
+            metadata: None,
+            external_account_keys: vec![],
+            challenge_types: vec![
+                ChallengeType::Http01,
+                ChallengeType::Dns01,
+                ChallengeType::TlsAlpn01,
+            ],
+            algorithms: JwsAlgorithm::ACME_ALGORITHMS.to_vec(),
+            order_lifetime: Duration::days(7),
+            authorization_lifetime: Duration::days(30),
+            orders_page_size: NonZeroUsize::new(100).unwrap(),
+            nonces: NonceService::new(10_000, std::time::Duration::from_secs(3600)),
+            random: SystemRandom::new(),
+        }
+    }
+
+    /// Sets the directory metadata
+    ///
+    /// New accounts must agree to the terms of service if `terms_of_service` is set, and must be
+    /// bound to an external account if `external_account_required` is true.
+    pub fn with_metadata(self, metadata: DirectoryMetadata) -> AcmeProvider<S> {
+        AcmeProvider {
+            metadata: Some(metadata),
+            ..self
+        }
+    }
+
+    /// Adds an external account key with which new accounts can be bound
+    pub fn with_external_account_key(mut self, key: ExternalAccountBinding) -> AcmeProvider<S> {
+        self.external_account_keys.push(key);
+        self
+    }
+
+    /// Sets the challenge types offered for each authorization (default: http-01, dns-01,
+    /// tls-alpn-01)
+    pub fn with_challenge_types(self, challenge_types: Vec<ChallengeType>) -> AcmeProvider<S> {
+        AcmeProvider {
+            challenge_types,
+            ..self
+        }
+    }
+
+    /// Sets the signature algorithms accepted for requests (default: RS256, ES256, ES384, EdDSA)
+    ///
+    /// Algorithms which requests cannot be verified with, i.e. those not listed in
+    /// [`JwsAlgorithm::ACME_ALGORITHMS`], are ignored.
+    pub fn with_algorithms(self, mut algorithms: Vec<JwsAlgorithm>) -> AcmeProvider<S> {
+        algorithms.retain(|algorithm| JwsAlgorithm::ACME_ALGORITHMS.contains(algorithm));
+        AcmeProvider { algorithms, ..self }
+    }
+
+    /// Sets the lifetimes of new orders (default: 7 days) and authorizations (default: 30 days)
+    pub fn with_lifetimes(self, order: Duration, authorization: Duration) -> AcmeProvider<S> {
+        AcmeProvider {
+            order_lifetime: order,
+            authorization_lifetime: authorization,
+            ..self
+        }
+    }
+
+    /// Sets the number of orders per page of an account's orders list (default: 100)
+    pub fn with_orders_page_size(self, orders_page_size: NonZeroUsize) -> AcmeProvider<S> {
+        AcmeProvider {
+            orders_page_size,
+            ..self
+        }
+    }
+
+    /// Sets the nonce service (default: a stateful service remembering up to 10000 nonces for an
+    /// hour)
+    pub fn with_nonce_service(self, nonces: NonceService) -> AcmeProvider<S> {
+        AcmeProvider { nonces, ..self }
+    }
+
+    /// Returns the provider's store
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Returns the URL of a resource
+    pub fn url(&self, resource: &ProviderResource) -> String {
+        let path = match resource {
+            ProviderResource::Directory => "directory".to_string(),
+            ProviderResource::NewNonce => "new-nonce".to_string(),
+            ProviderResource::NewAccount => "new-account".to_string(),
+            ProviderResource::NewOrder => "new-order".to_string(),
+            ProviderResource::RevokeCertificate => "revoke-cert".to_string(),
+            ProviderResource::KeyChange => "key-change".to_string(),
+            ProviderResource::Account(id) => format!("account/{}", id),
+            ProviderResource::AccountOrders(id, 0) => format!("account/{}/orders", id),
+            ProviderResource::AccountOrders(id, page) => {
+                format!("account/{}/orders?page={}", id, page)
+            }
+            ProviderResource::Order(id) => format!("order/{}", id),
+            ProviderResource::Finalize(id) => format!("order/{}/finalize", id),
+            ProviderResource::Authorization(id) => format!("authorization/{}", id),
+            ProviderResource::Challenge(id) => format!("challenge/{}", id),
+            ProviderResource::Certificate(id) => format!("certificate/{}", id),
+        };
+
+        format!("{}/{}", self.base_url, path)
+    }
+
+    /// Resolves a request URL to the resource it identifies
+    pub fn resource(&self, url: &str) -> Option<ProviderResource> {
+        let path = url
+            .strip_prefix(self.base_url.as_str())?
+            .strip_prefix('/')?;
+        let (path, page) = match path.split_once('?') {
+            Some((path, query)) => (path, Some(query.strip_prefix("page=")?.parse().ok()?)),
+            None => (path, None),
+        };
+        let segments: Vec<&str> = path.split('/').collect();
+
+        if segments.iter().any(|segment| segment.is_empty()) {
+            return None;
+        }
+
+        Some(match (segments.as_slice(), page) {
+            (["account", id, "orders"], page) if page != Some(0) => {
+                ProviderResource::AccountOrders(id.to_string(), page.unwrap_or(0))
+            }
+            (_, Some(_)) => return None,
+            (segments, None) => match segments {
+                ["directory"] => ProviderResource::Directory,
+                ["new-nonce"] => ProviderResource::NewNonce,
+                ["new-account"] => ProviderResource::NewAccount,
+                ["new-order"] => ProviderResource::NewOrder,
+                ["revoke-cert"] => ProviderResource::RevokeCertificate,
+                ["key-change"] => ProviderResource::KeyChange,
+                ["account", id] => ProviderResource::Account(id.to_string()),
+                ["order", id] => ProviderResource::Order(id.to_string()),
+                ["order", id, "finalize"] => ProviderResource::Finalize(id.to_string()),
+                ["authorization", id] => ProviderResource::Authorization(id.to_string()),
+                ["challenge", id] => ProviderResource::Challenge(id.to_string()),
+                ["certificate", id] => ProviderResource::Certificate(id.to_string()),
+                _ => return None,
+            },
+        })
+    }
+
+    /// Returns the directory
+    ///
+    /// For more information, refer to [RFC 8555 § 7.1.1](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.1)
+    pub fn directory(&self) -> ProviderResult<Directory> {
+        Ok(Directory {
+            new_nonce: NewNonceUrl::new(self.url(&ProviderResource::NewNonce)),
+            new_account: NewAccountUrl::new(self.url(&ProviderResource::NewAccount)),
+            new_order: NewOrderUrl::new(self.url(&ProviderResource::NewOrder)),
+            new_authorization: None,
+            revoke_certificate: RevokeCertificateUrl::new(
+                self.url(&ProviderResource::RevokeCertificate),
+            ),
+            key_change: KeyChangeUrl::new(self.url(&ProviderResource::KeyChange)),
+            renewal_info: None,
+            metadata: self.metadata.clone(),
+        })
+    }
+
+    /// Issues a nonce, to be returned in the `Replay-Nonce` header field of every response
+    ///
+    /// For more information, refer to [RFC 8555 § 7.2](https://datatracker.ietf.org/doc/html/rfc8555#section-7.2)
+    pub fn new_nonce(&self) -> ProviderResult<String> {
+        self.nonces.issue().map_err(boxed)
+    }
+
+    /// Creates an account, or returns the existing account for the request key
+    ///
+    /// Returns the account URL, the account and whether the account was created. Existing
+    /// accounts which are no longer valid are rejected with an `unauthorized` error.
+    ///
+    /// For more information, refer to [RFC 8555 § 7.3](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3)
+    pub fn new_account(
+        &self,
+        request: &SignedRequest<NewAccount>,
+    ) -> ProviderResult<(AccountUrl, Account, bool)> {
+        self.check_request(request, &self.url(&ProviderResource::NewAccount))?;
+
+        let key = request.protected.json_web_key.as_ref().ok_or_else(|| {
+            error(
+                ErrorType::Malformed,
+                "new account requests must be signed with a jwk",
+            )
+        })?;
+        request.verify(key).map_err(boxed)?;
+
+        if let Some(id) = self.store.account_by_thumbprint(&key.thumbprint())? {
+            return self.existing_account(&id);
+        }
+
+        let new_account = request.require_payload().map_err(boxed)?;
+
+        if new_account.only_return_existing == Some(true) {
+            return Err(error(
+                ErrorType::AccountDoesNotExist,
+                "no account exists for the key",
+            ));
+        }
+
+        for contact in new_account.contact.iter().flatten() {
+            contact.check_supported().map_err(boxed)?;
+        }
+
+        let metadata = self.metadata.as_ref();
+
+        if metadata.is_some_and(|metadata| metadata.terms_of_service.is_some())
+            && new_account.terms_of_service_agreed != Some(true)
+        {
+            return Err(error(
+                ErrorType::Malformed,
+                "the terms of service must be agreed to",
+            ));
+        }
+
+        match &new_account.external_account_binding {
+            Some(binding) => {
+                let key_id = ExternalAccountBinding::binding_key_id(binding).map_err(boxed)?;
+                self.external_account_keys
+                    .iter()
+                    .find(|key| key.key_id == key_id)
+                    .ok_or_else(|| error(ErrorType::Unauthorized, "unknown external account key"))?
+                    .verify(binding, &request.protected)
+                    .map_err(boxed)?;
+            }
+            None if metadata
+                .is_some_and(|metadata| metadata.external_account_required == Some(true)) =>
+            {
+                return Err(error(
+                    ErrorType::ExternalAccountRequired,
+                    "an external account binding is required",
+                ))
+            }
+            None => (),
+        }
+
+        let id = self.random_id()?;
+        let account = Account {
+            status: AccountStatus::Valid,
+            contact: new_account.contact.clone(),
+            terms_of_service_agreed: new_account.terms_of_service_agreed,
+            external_account_binding: new_account.external_account_binding.clone(),
+            orders: AccountOrdersUrl::new(
+                self.url(&ProviderResource::AccountOrders(id.clone(), 0)),
+            ),
+        };
+        let record = AccountRecord {
+            key: key.to_public(),
+            account: account.clone(),
+        };
+
+        // Another request may have created an account for the key in the meantime
+        if let Some(existing) = self.store.insert_account(&id, record)? {
+            return self.existing_account(&existing);
+        }
+
+        Ok((self.account_url(&id), account, true))
+    }
+
+    /// Returns (for POST-as-GET requests) or updates an account
+    ///
+    /// For more information, refer to [RFC 8555 § 7.3.2](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3.2)
+    pub fn account(
+        &self,
+        id: &str,
+        request: &SignedRequest<AccountUpdate>,
+    ) -> ProviderResult<Account> {
+        let (account_id, record) =
+            self.authenticate(request, &ProviderResource::Account(id.to_string()))?;
+        check_owner(&account_id, id)?;
+
+        let Some(update) = &request.payload else {
+            return Ok(record.account);
+        };
+
+        for contact in update.contact.iter().flatten() {
+            contact.check_supported().map_err(boxed)?;
+        }
+
+        if update
+            .status
+            .is_some_and(|status| status != AccountStatus::Deactivated)
+        {
+            return Err(error(
+                ErrorType::Malformed,
+                "accounts can only be updated to deactivated",
+            ));
+        }
+
+        let record = self.update_account(id, &mut |account| {
+            if let Some(contact) = &update.contact {
+                account.contact = Some(contact.clone());
+            }
+
+            if let Some(agreed) = update.terms_of_service_agreed {
+                account.terms_of_service_agreed = Some(agreed);
+            }
+
+            if update.status.is_some() {
+                account.status = account
+                    .status
+                    .transition(AccountEvent::Deactivate)
+                    .map_err(boxed)?;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(record.account)
+    }
+
+    /// Rolls over an account key, returning the account
+    ///
+    /// The request is signed by the current key and carries the inner JSON web signature, signed
+    /// by the new key, which must not be in use by another account.
+    ///
+    /// For more information, refer to [RFC 8555 § 7.3.5](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3.5)
+    pub fn key_change(&self, request: &SignedRequest<JsonWebSignature>) -> ProviderResult<Account> {
+        let (account_id, record) = self.authenticate(request, &ProviderResource::KeyChange)?;
+
+        request
+            .require_payload()
+            .map_err(boxed)?
+            .decode_protected()
+            .map_err(boxed)?
+            .check_algorithm(&self.algorithms)
+            .map_err(boxed)?;
+
+        let key = KeyChange::verify(request, &record.key).map_err(boxed)?;
+
+        if !self
+            .store
+            .replace_account_key(&account_id, key.to_public())?
+        {
+            return Err(error(
+                ErrorType::Malformed,
+                "the new key is already in use by an account",
+            ));
+        }
+
+        Ok(record.account)
+    }
+
+    /// Returns a (zero-based) page of the account's orders, oldest first, along with the URL of
+    /// the next page
+    ///
+    /// For more information, refer to [RFC 8555 § 7.1.2.1](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.2.1)
+    pub fn account_orders(
+        &self,
+        id: &str,
+        page: usize,
+        request: &SignedRequest<Value>,
+    ) -> ProviderResult<AccountOrdersPage> {
+        let (account_id, _) = self.authenticate(
+            request,
+            &ProviderResource::AccountOrders(id.to_string(), page),
+        )?;
+        check_owner(&account_id, id)?;
+
+        let orders = self
+            .store
+            .account_orders(id)?
+            .into_iter()
+            .map(|order| OrderUrl::new(self.url(&ProviderResource::Order(order))))
+            .collect::<Vec<_>>();
+
+        AccountOrders::paginate(&orders, page, self.orders_page_size, |page| {
+            AccountOrdersUrl::new(self.url(&ProviderResource::AccountOrders(id.to_string(), page)))
+        })
+        .ok_or_else(|| not_found("page"))
+    }
+
+    /// Creates an order, along with a pending authorization for each identifier
+    ///
+    /// Identifiers are validated and normalized first, and rejected with the error type of the
+    /// corresponding [`IdentifierError`](super::IdentifierError). Wildcard identifiers are
+    /// authorized for their base domain, using dns-01 challenges only.
+    ///
+    /// For more information, refer to [RFC 8555 § 7.4](https://datatracker.ietf.org/doc/html/rfc8555#section-7.4)
+    pub fn new_order(
+        &self,
+        request: &SignedRequest<NewOrder>,
+    ) -> ProviderResult<(OrderUrl, Order)> {
+        let (account_id, _) = self.authenticate(request, &ProviderResource::NewOrder)?;
+        let new_order = request.require_payload().map_err(boxed)?;
+
+        if new_order.identifiers.is_empty() {
+            return Err(error(
+                ErrorType::Malformed,
+                "orders must contain at least one identifier",
+            ));
+        }
+
+        let identifiers = new_order
+            .identifiers
+            .iter()
+            .map(Identifier::normalize)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(boxed)?;
+
+        let now = OffsetDateTime::now_utc();
+        let mut authorizations = vec![];
+        let mut authorization_urls = vec![];
+
+        for identifier in &identifiers {
+            let id = self.new_authorization(&account_id, identifier, now)?;
+            authorization_urls.push(super::AuthorizationUrl::new(
+                self.url(&ProviderResource::Authorization(id.clone())),
+            ));
+            authorizations.push(id);
+        }
+
+        let id = self.random_id()?;
+        let order = Order {
+            status: OrderStatus::Pending,
+            expires: Some(Timestamp::from(now + self.order_lifetime)),
+            identifiers,
+            not_before: new_order.not_before.clone(),
+            not_after: new_order.not_after.clone(),
+            error: None,
+            authorizations: authorization_urls,
+            finalize: super::FinalizeUrl::new(self.url(&ProviderResource::Finalize(id.clone()))),
+            certificate: None,
+        };
+        self.store.insert_order(
+            &id,
+            OrderRecord {
+                account: account_id,
+                order: order.clone(),
+                authorizations,
+                certificate_signing_request: None,
+            },
+        )?;
+
+        Ok((OrderUrl::new(self.url(&ProviderResource::Order(id))), order))
+    }
+
+    /// Returns an order, which becomes `ready` once all of its authorizations are valid or
+    /// `invalid` once one of them is no longer pending or valid
+    ///
+    /// For more information, refer to [RFC 8555 § 7.4](https://datatracker.ietf.org/doc/html/rfc8555#section-7.4)
+    pub fn order(&self, id: &str, request: &SignedRequest<Value>) -> ProviderResult<Order> {
+        let (account_id, _) =
+            self.authenticate(request, &ProviderResource::Order(id.to_string()))?;
+        let record = self.require_order(id)?;
+        check_owner(&account_id, &record.account)?;
+
+        Ok(self.refresh_order(id, record)?.order)
+    }
+
+    /// Finalizes a `ready` order, which becomes `processing` until the caller issues the
+    /// certificate with `issue_certificate` or fails the order with `fail_order`
+    ///
+    /// The CSR is stored in the order record; it is not checked against the order identifiers.
+    ///
+    /// For more information, refer to [RFC 8555 § 7.4](https://datatracker.ietf.org/doc/html/rfc8555#section-7.4)
+    pub fn finalize(
+        &self,
+        id: &str,
+        request: &SignedRequest<OrderFinalize>,
+    ) -> ProviderResult<Order> {
+        let (account_id, _) =
+            self.authenticate(request, &ProviderResource::Finalize(id.to_string()))?;
+        let record = self.require_order(id)?;
+        check_owner(&account_id, &record.account)?;
+        self.refresh_order(id, record)?;
+
+        let finalize = request.require_payload().map_err(boxed)?;
+        let csr = URL_SAFE_NO_PAD
+            .decode(&finalize.certificate_signing_request)
+            .ok()
+            .filter(|csr| !csr.is_empty())
+            .ok_or_else(|| {
+                error(
+                    ErrorType::BadCertificateSigningRequest,
+                    "CSR is not valid base64url",
+                )
+            })?;
+
+        let record = self.update_order(id, &mut |record| {
+            record.order.status = record
+                .order
+                .status
+                .transition(OrderEvent::Finalize)
+                .map_err(boxed)?;
+            record.certificate_signing_request = Some(csr.clone());
+
+            Ok(())
+        })?;
+
+        Ok(record.order)
+    }
+
+    /// Returns (for POST-as-GET requests) or deactivates an authorization
+    ///
+    /// For more information, refer to [RFC 8555 § 7.5](https://datatracker.ietf.org/doc/html/rfc8555#section-7.5)
+    pub fn authorization(
+        &self,
+        id: &str,
+        request: &SignedRequest<AuthorizationUpdate>,
+    ) -> ProviderResult<Authorization> {
+        let (account_id, _) =
+            self.authenticate(request, &ProviderResource::Authorization(id.to_string()))?;
+        let record = self.require_authorization(id)?;
+        check_owner(&account_id, &record.account)?;
+        let mut record = self.refresh_authorization(id, record)?;
+
+        if let Some(update) = &request.payload {
+            if update.status != AuthorizationStatus::Deactivated {
+                return Err(error(
+                    ErrorType::Malformed,
+                    "authorizations can only be updated to deactivated",
+                ));
+            }
+
+            record = self.update_authorization(id, &mut |record| {
+                record.status = record
+                    .status
+                    .transition(AuthorizationEvent::Deactivate)
+                    .map_err(boxed)?;
+
+                Ok(())
+            })?;
+        }
+
+        self.render_authorization(record)
+    }
+
+    /// Returns (for POST-as-GET requests) or responds to a challenge, which becomes `processing`
+    /// until the caller reports the validation result with `complete_challenge`
+    ///
+    /// For more information, refer to [RFC 8555 § 7.5.1](https://datatracker.ietf.org/doc/html/rfc8555#section-7.5.1)
+    pub fn challenge(&self, id: &str, request: &SignedRequest<Value>) -> ProviderResult<Challenge> {
+        let (account_id, _) =
+            self.authenticate(request, &ProviderResource::Challenge(id.to_string()))?;
+        let record = self.require_challenge(id)?;
+        let authorization = self.require_authorization(&record.authorization)?;
+        check_owner(&account_id, &authorization.account)?;
+        let authorization = self.refresh_authorization(&record.authorization, authorization)?;
+
+        if request.payload.is_none()
+            || record.challenge.status != ChallengeStatus::Pending
+            || authorization.status != AuthorizationStatus::Pending
+        {
+            return Ok(record.challenge);
+        }
+
+        let record = self.update_challenge(id, &mut |record| {
+            // The challenge may have been responded to concurrently
+            if record.challenge.status == ChallengeStatus::Pending {
+                record.challenge.status = record
+                    .challenge
+                    .status
+                    .transition(ChallengeEvent::Respond)
+                    .map_err(boxed)?;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(record.challenge)
+    }
+
+    /// Returns a certificate chain
+    ///
+    /// For more information, refer to [RFC 8555 § 7.4.2](https://datatracker.ietf.org/doc/html/rfc8555#section-7.4.2)
+    pub fn certificate(&self, id: &str, request: &SignedRequest<Value>) -> ProviderResult<String> {
+        let (account_id, _) =
+            self.authenticate(request, &ProviderResource::Certificate(id.to_string()))?;
+        let record = self
+            .store
+            .certificate(id)?
+            .ok_or_else(|| not_found("certificate"))?;
+        check_owner(&account_id, &record.account)?;
+
+        Ok(record.chain)
+    }
+
+    /// Records the result of validating a `processing` challenge, updating its authorization
+    ///
+    /// Authorizations which are no longer pending (e.g. deactivated or expired) are left as is.
+    pub fn complete_challenge(
+        &self,
+        id: &str,
+        validation: Result<(), super::Error>,
+    ) -> ProviderResult<Challenge> {
+        let (challenge_event, authorization_event) = match validation {
+            Ok(()) => (ChallengeEvent::Validate, AuthorizationEvent::ChallengeValid),
+            Err(_) => (ChallengeEvent::Fail, AuthorizationEvent::Fail),
+        };
+        let validated = Timestamp::from(OffsetDateTime::now_utc());
+
+        let record = self.update_challenge(id, &mut |record| {
+            record.challenge.status = record
+                .challenge
+                .status
+                .transition(challenge_event)
+                .map_err(boxed)?;
+            match &validation {
+                Ok(()) => record.challenge.validated = Some(validated.clone()),
+                Err(error) => record.challenge.error = Some(error.clone()),
+            }
+
+            Ok(())
+        })?;
+
+        self.update_authorization(&record.authorization, &mut |authorization| {
+            if authorization.status == AuthorizationStatus::Pending {
+                authorization.status = authorization
+                    .status
+                    .transition(authorization_event)
+                    .map_err(boxed)?;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(record.challenge)
+    }
+
+    /// Revokes a certificate
+    ///
+    /// The request must be signed either by the account which ordered the certificate (`kid`) or
+    /// by the certificate key (`jwk`).
+    ///
+    /// For more information, refer to [RFC 8555 § 7.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.6)
+    pub fn revoke_certificate(
+        &self,
+        request: &SignedRequest<CertificateRevocation>,
+    ) -> ProviderResult<()> {
+        let account = match &request.protected.json_web_key {
+            Some(key) => {
+                self.check_request(request, &self.url(&ProviderResource::RevokeCertificate))?;
+                request.verify(key).map_err(boxed)?;
+                None
+            }
+            None => Some(
+                self.authenticate(request, &ProviderResource::RevokeCertificate)?
+                    .0,
+            ),
+        };
+
+        let revocation = request.require_payload().map_err(boxed)?;
+        let certificate = URL_SAFE_NO_PAD
+            .decode(&revocation.certificate)
+            .map_err(|_| error(ErrorType::Malformed, "certificate is not valid base64url"))?;
+        let id = self
+            .store
+            .certificate_by_leaf(&certificate)?
+            .ok_or_else(|| not_found("certificate"))?;
+        let record = self
+            .store
+            .certificate(&id)?
+            .ok_or_else(|| not_found("certificate"))?;
+
+        match (&account, &request.protected.json_web_key) {
+            (Some(account), _) => check_owner(account, &record.account)?,
+            (None, Some(key)) if certificate_key_matches(&certificate, key) => (),
+            _ => {
+                return Err(error(
+                    ErrorType::Unauthorized,
+                    "the request key is not the certificate key",
+                ))
+            }
+        }
+
+        let reason = revocation
+            .reason
+            .unwrap_or(CertificateRevocationReason::Unspecified);
+
+        self.update_certificate(&id, &mut |record| {
+            if record.revoked.is_some() {
+                return Err(error(
+                    ErrorType::AlreadyRevoked,
+                    "certificate is already revoked",
+                ));
+            }
+
+            record.revoked = Some(reason);
+
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    /// Records a failed attempt at validating a `processing` challenge, which remains `processing`
+    /// until the caller reports the result of a later attempt
+    ///
+    /// For more information, refer to [RFC 8555 § 8.2](https://datatracker.ietf.org/doc/html/rfc8555#section-8.2)
+    pub fn retry_challenge(&self, id: &str, error: super::Error) -> ProviderResult<Challenge> {
+        let record = self.update_challenge(id, &mut |record| {
+            record.challenge.status = record
+                .challenge
+                .status
+                .transition(ChallengeEvent::Retry)
+                .map_err(boxed)?;
+            record.challenge.error = Some(error.clone());
+
+            Ok(())
+        })?;
+
+        Ok(record.challenge)
+    }
+
+    /// Records the certificate chain issued for a `processing` order, which becomes `valid`
+    ///
+    /// The chain must be in the `application/pem-certificate-chain` format.
+    pub fn issue_certificate(&self, order: &str, chain: String) -> ProviderResult<Order> {
+        CertificateChain::from_pem(&chain).map_err(boxed)?;
+        let id = self.random_id()?;
+        let certificate = CertificateUrl::new(self.url(&ProviderResource::Certificate(id.clone())));
+
+        let record = self.update_order(order, &mut |record| {
+            record.order.status = record
+                .order
+                .status
+                .transition(OrderEvent::CertificateIssued)
+                .map_err(boxed)?;
+            record.order.certificate = Some(certificate.clone());
+
+            Ok(())
+        })?;
+
+        self.store.insert_certificate(
+            &id,
+            CertificateRecord {
+                account: record.account.clone(),
+                chain,
+                revoked: None,
+            },
+        )?;
+
+        Ok(record.order)
+    }
+
+    /// Records an error which prevents an order from being fulfilled, which becomes `invalid`
+    pub fn fail_order(&self, order: &str, error: super::Error) -> ProviderResult<Order> {
+        let record = self.update_order(order, &mut |record| {
+            record.order.status = record
+                .order
+                .status
+                .transition(OrderEvent::Fail)
+                .map_err(boxed)?;
+            record.order.error = Some(error.clone());
+
+            Ok(())
+        })?;
+
+        Ok(record.order)
+    }
+
+    fn authenticate<T>(
+        &self,
+        request: &SignedRequest<T>,
+        resource: &ProviderResource,
+    ) -> ProviderResult<(String, AccountRecord)> {
+        self.check_request(request, &self.url(resource))?;
+
+        let id = match (&request.protected.key_id, &request.protected.json_web_key) {
+            (Some(key_id), None) => match self.resource(key_id) {
+                Some(ProviderResource::Account(id)) => id,
+                _ => return Err(error(ErrorType::AccountDoesNotExist, "unknown account")),
+            },
+            _ => {
+                return Err(error(
+                    ErrorType::Malformed,
+                    "requests must be signed with a kid",
+                ))
+            }
+        };
+
+        let record = self
+            .store
+            .account(&id)?
+            .ok_or_else(|| error(ErrorType::AccountDoesNotExist, "unknown account"))?;
+        check_valid(&record)?;
+
+        request.verify(&record.key).map_err(boxed)?;
+
+        Ok((id, record))
+    }
+
+    fn new_authorization(
+        &self,
+        account: &str,
+        identifier: &Identifier,
+        now: OffsetDateTime,
+    ) -> ProviderResult<String> {
+        let wildcard = identifier.is_wildcard();
+        let types: Vec<&ChallengeType> = self
+            .challenge_types
+            .iter()
+            .filter(|type_| identifier.supports_challenge(type_))
+            .filter(|type_| !wildcard || **type_ == ChallengeType::Dns01)
+            .collect();
+
+        if types.is_empty() {
+            return Err(error(
+                ErrorType::UnsupportedIdentifier,
+                format!("no challenge can validate identifier {}", identifier.value),
+            ));
+        }
+
+        let id = self.random_id()?;
+        let mut challenges = vec![];
+
+        for type_ in types {
+            let challenge = self.random_id()?;
+            self.store.insert_challenge(
+                &challenge,
+                ChallengeRecord {
+                    authorization: id.clone(),
+                    challenge: Challenge {
+                        url: ChallengeUrl::new(
+                            self.url(&ProviderResource::Challenge(challenge.clone())),
+                        ),
+                        type_: type_.clone(),
+                        status: ChallengeStatus::Pending,
+                        token: Some(self.random(32)?),
+                        validated: None,
+                        error: None,
+                    },
+                },
+            )?;
+            challenges.push(challenge);
+        }
+
+        self.store.insert_authorization(
+            &id,
+            AuthorizationRecord {
+                account: account.to_string(),
+                identifier: Identifier {
+                    type_: identifier.type_,
+                    value: identifier.base_domain().to_string(),
+                },
+                status: AuthorizationStatus::Pending,
+                expires: Some(now + self.authorization_lifetime),
+                wildcard: wildcard.then_some(true),
+                challenges,
+            },
+        )?;
+
+        Ok(id)
+    }
+
+    fn refresh_order(&self, id: &str, record: OrderRecord) -> ProviderResult<OrderRecord> {
+        let now = OffsetDateTime::now_utc();
+        let mut statuses = vec![];
+
+        if record.order.status == OrderStatus::Pending && !record.order.is_expired(now) {
+            for authorization in &record.authorizations {
+                let record = self.require_authorization(authorization)?;
+                statuses.push(self.refresh_authorization(authorization, record)?.status);
+            }
+        }
+
+        if !refresh_order(&mut record.clone(), now, &statuses)? {
+            return Ok(record);
+        }
+
+        self.update_order(id, &mut |record| {
+            refresh_order(record, now, &statuses).map(|_| ())
+        })
+    }
+
+    fn refresh_authorization(
+        &self,
+        id: &str,
+        record: AuthorizationRecord,
+    ) -> ProviderResult<AuthorizationRecord> {
+        let now = OffsetDateTime::now_utc();
+
+        if !expire_authorization(&mut record.clone(), now)? {
+            return Ok(record);
+        }
+
+        self.update_authorization(id, &mut |record| {
+            expire_authorization(record, now).map(|_| ())
+        })
+    }
+
+    fn render_authorization(&self, record: AuthorizationRecord) -> ProviderResult<Authorization> {
+        let mut challenges = vec![];
+        for challenge in &record.challenges {
+            challenges.push(self.require_challenge(challenge)?.challenge);
+        }
+
+        Ok(Authorization {
+            identifier: record.identifier,
+            status: record.status,
+            expires: record.expires.map(Timestamp::from),
+            challenges,
+            wildcard: record.wildcard,
+        })
+    }
+
+    fn require_account(&self, id: &str) -> ProviderResult<AccountRecord> {
+        self.store.account(id)?.ok_or_else(|| not_found("account"))
+    }
+
+    fn require_order(&self, id: &str) -> ProviderResult<OrderRecord> {
+        self.store.order(id)?.ok_or_else(|| not_found("order"))
+    }
+
+    fn require_authorization(&self, id: &str) -> ProviderResult<AuthorizationRecord> {
+        self.store
+            .authorization(id)?
+            .ok_or_else(|| not_found("authorization"))
+    }
+
+    fn require_challenge(&self, id: &str) -> ProviderResult<ChallengeRecord> {
+        self.store
+            .challenge(id)?
+            .ok_or_else(|| not_found("challenge"))
+    }
+
+    fn existing_account(&self, id: &str) -> ProviderResult<(AccountUrl, Account, bool)> {
+        let record = self.require_account(id)?;
+        check_valid(&record)?;
+
+        Ok((self.account_url(id), record.account, false))
+    }
+
+    fn update_account(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut Account) -> ProviderResult<()>,
+    ) -> ProviderResult<AccountRecord> {
+        self.store
+            .update_account(id, update)?
+            .ok_or_else(|| not_found("account"))
+    }
+
+    fn update_order(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut OrderRecord) -> ProviderResult<()>,
+    ) -> ProviderResult<OrderRecord> {
+        self.store
+            .update_order(id, update)?
+            .ok_or_else(|| not_found("order"))
+    }
+
+    fn update_authorization(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut AuthorizationRecord) -> ProviderResult<()>,
+    ) -> ProviderResult<AuthorizationRecord> {
+        self.store
+            .update_authorization(id, update)?
+            .ok_or_else(|| not_found("authorization"))
+    }
+
+    fn update_challenge(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut ChallengeRecord) -> ProviderResult<()>,
+    ) -> ProviderResult<ChallengeRecord> {
+        self.store
+            .update_challenge(id, update)?
+            .ok_or_else(|| not_found("challenge"))
+    }
+
+    fn update_certificate(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut CertificateRecord) -> ProviderResult<()>,
+    ) -> ProviderResult<CertificateRecord> {
+        self.store
+            .update_certificate(id, update)?
+            .ok_or_else(|| not_found("certificate"))
+    }
+
+    fn account_url(&self, id: &str) -> AccountUrl {
+        AccountUrl::new(self.url(&ProviderResource::Account(id.to_string())))
+    }
+
+    fn random_id(&self) -> ProviderResult<String> {
+        self.random(16)
+    }
+
+    fn check_request<T>(&self, request: &SignedRequest<T>, url: &str) -> ProviderResult<()> {
+        if request.protected.url != url {
+            return Err(error(
+                ErrorType::Unauthorized,
+                "JWS url does not match the request URL",
+            ));
+        }
+
+        request
+            .protected
+            .check_algorithm(&self.algorithms)
+            .map_err(boxed)?;
+
+        self.nonces
+            .consume_request(&request.protected)
+            .map_err(boxed)
+    }
+
+    fn random(&self, len: usize) -> ProviderResult<String> {
+        let mut bytes = vec![0; len];
+        self.random
+            .fill(&mut bytes)
+            .map_err(|_| error(ErrorType::ServerInternal, "random number generator failed"))?;
+
+        Ok(URL_SAFE_NO_PAD.encode(bytes))
+    }
+}
+
+fn check_owner(account: &str, owner: &str) -> ProviderResult<()> {
+    match account == owner {
+        true => Ok(()),
+        false => Err(error(
+            ErrorType::Unauthorized,
+            "resource belongs to another account",
+        )),
+    }
+}
+
+/// Fails the order once expired, or applies the status of its authorizations to a pending order,
+/// returning true if the order changed
+fn refresh_order(
+    record: &mut OrderRecord,
+    now: OffsetDateTime,
+    authorizations: &[AuthorizationStatus],
+) -> ProviderResult<bool> {
+    match record.order.status {
+        OrderStatus::Pending | OrderStatus::Ready if record.order.is_expired(now) => {
+            record.order.status = record
+                .order
+                .status
+                .transition(OrderEvent::Fail)
+                .map_err(boxed)?;
+            record.order.error = Some(super::Error::new(
+                ErrorType::Unauthorized,
+                "the order has expired",
+            ));
+
+            return Ok(true);
+        }
+        OrderStatus::Pending => (),
+        _ => return Ok(false),
+    }
+
+    if authorizations
+        .iter()
+        .all(|status| *status == AuthorizationStatus::Valid)
+    {
+        record.order.status = record
+            .order
+            .status
+            .transition(OrderEvent::AuthorizationsValid)
+            .map_err(boxed)?;
+    } else if let Some(status) = authorizations.iter().find(|status| status.is_terminal()) {
+        record.order.status = record
+            .order
+            .status
+            .transition(OrderEvent::Fail)
+            .map_err(boxed)?;
+        record.order.error = Some(super::Error::new(
+            ErrorType::Unauthorized,
+            format!("an authorization is {}", status.as_str()),
+        ));
+    } else {
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+/// Expires the authorization once its expiration time has passed, returning true if the
+/// authorization changed
+fn expire_authorization(
+    record: &mut AuthorizationRecord,
+    now: OffsetDateTime,
+) -> ProviderResult<bool> {
+    if record.status.is_terminal() || record.expires.is_none_or(|expires| expires > now) {
+        return Ok(false);
+    }
+
+    record.status = record
+        .status
+        .transition(AuthorizationEvent::Expire)
+        .map_err(boxed)?;
+
+    Ok(true)
+}
+
+fn check_valid(record: &AccountRecord) -> ProviderResult<()> {
+    match record.account.status {
+        AccountStatus::Valid => Ok(()),
+        status => Err(error(
+            ErrorType::Unauthorized,
+            format!("account is {}", status.as_str()),
+        )),
+    }
+}
+
+/// Returns true if the DER-encoded certificate holds the public key of the JSON web key
+fn certificate_key_matches(certificate: &[u8], key: &JsonWebKey) -> bool {
+    let decode = |value: &str| URL_SAFE_NO_PAD.decode(value).ok();
+    let Some(public_key) = super::der::subject_public_key(certificate) else {
+        return false;
+    };
+
+    match key {
+        JsonWebKey::Rsa(key) => {
+            let integers = rsa_public_key(public_key);
+            let unsigned = |integer: &[u8]| -> Vec<u8> {
+                integer.iter().copied().skip_while(|b| *b == 0).collect()
+            };
+
+            match (integers, decode(&key.modulus), decode(&key.exponent)) {
+                (Some((modulus, exponent)), Some(n), Some(e)) => {
+                    unsigned(modulus) == unsigned(&n) && unsigned(exponent) == unsigned(&e)
+                }
+                _ => false,
+            }
+        }
+        JsonWebKey::Ec(key) => match (decode(&key.x), decode(&key.y)) {
+            (Some(x), Some(y)) => public_key == [&[0x04][..], &x, &y].concat(),
+            _ => false,
+        },
+        JsonWebKey::Okp(key) => decode(&key.x).is_some_and(|x| public_key == x),
+    }
+}
+
+/// Returns the modulus and public exponent of a DER-encoded `RSAPublicKey`
+fn rsa_public_key(public_key: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (key, _) = super::der::expect(public_key, super::der::TAG_SEQUENCE)?;
+    let (modulus, key) = super::der::expect(key, super::der::TAG_INTEGER)?;
+    let (exponent, _) = super::der::expect(key, super::der::TAG_INTEGER)?;
+    Some((modulus, exponent))
+}
+
+fn error(type_: ErrorType, detail: impl Into<String>) -> Box<super::Error> {
+    Box::new(super::Error::new(type_, detail))
+}
+
+fn not_found(resource: &str) -> Box<super::Error> {
+    let mut error = error(ErrorType::Malformed, format!("{} does not exist", resource));
+    error.status = Some(404);
+    error
+}
+
+fn boxed(error: impl Into<super::Error>) -> Box<super::Error> {
+    Box::new(error.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::DeserializeOwned;
+    use serde_json::json;
+
+    use super::*;
+    use crate::v2::{JsonWebSignatureProtected, MemoryStore, SigningKey};
+
+    const BASE_URL: &str = "https://acme.example.com/acme";
+
+    fn provider() -> AcmeProvider<MemoryStore> {
+        AcmeProvider::new(BASE_URL, MemoryStore::new())
+    }
+
+    fn signing_key() -> SigningKey {
+        let pkcs8 = SigningKey::generate_pkcs8(&JwsAlgorithm::Es256).unwrap();
+        SigningKey::from_pkcs8(JwsAlgorithm::Es256, &pkcs8).unwrap()
+    }
+
+    fn request<T: DeserializeOwned>(
+        provider: &AcmeProvider<MemoryStore>,
+        key: &SigningKey,
+        account: Option<&AccountUrl>,
+        resource: &ProviderResource,
+        payload: Option<Value>,
+    ) -> SignedRequest<T> {
+        let protected = JsonWebSignatureProtected {
+            algorithm: key.algorithm(),
+            nonce: Some(provider.new_nonce().unwrap()),
+            url: provider.url(resource),
+            json_web_key: account.is_none().then(|| key.public_key()),
+            key_id: account.map(|account| account.as_str().to_string()),
+        };
+        let signature = match payload {
+            Some(payload) => JsonWebSignature::sign(&protected, &payload, key),
+            None => JsonWebSignature::sign_empty(&protected, key),
+        };
+
+        SignedRequest::decode(signature.unwrap()).unwrap()
+    }
+
+    fn new_account(provider: &AcmeProvider<MemoryStore>, key: &SigningKey) -> AccountUrl {
+        let request = request(
+            provider,
+            key,
+            None,
+            &ProviderResource::NewAccount,
+            Some(json!({"contact": ["mailto:admin@example.com"]})),
+        );
+
+        provider.new_account(&request).unwrap().0
+    }
+
+    fn id(provider: &AcmeProvider<MemoryStore>, url: &str) -> String {
+        match provider.resource(url) {
+            Some(
+                ProviderResource::Account(id)
+                | ProviderResource::Order(id)
+                | ProviderResource::Authorization(id)
+                | ProviderResource::Challenge(id)
+                | ProviderResource::Certificate(id),
+            ) => id,
+            resource => panic!("unexpected resource: {:?}", resource),
+        }
+    }
+
+    fn certificate() -> String {
+        include_str!("testdata/leaf.pem").to_string()
+    }
+
+    fn revocation(
+        provider: &AcmeProvider<MemoryStore>,
+        key: &SigningKey,
+        account: Option<&AccountUrl>,
+    ) -> SignedRequest<CertificateRevocation> {
+        let chain = CertificateChain::from_pem(&certificate()).unwrap();
+        request(
+            provider,
+            key,
+            account,
+            &ProviderResource::RevokeCertificate,
+            Some(json!({"certificate": URL_SAFE_NO_PAD.encode(chain.leaf()), "reason": 4})),
+        )
+    }
+
+    #[test]
+    fn issuance() {
+        let provider = provider();
+        let key = signing_key();
+        let account = new_account(&provider, &key);
+        let account = Some(&account);
+
+        let (order_url, order) = provider
+            .new_order(&request(
+                &provider,
+                &key,
+                account,
+                &ProviderResource::NewOrder,
+                Some(json!({"identifiers": [{"type": "dns", "value": "Example.COM."}]})),
+            ))
+            .unwrap();
+        let order_id = id(&provider, order_url.as_str());
+
+        assert_eq!(order.status, OrderStatus::Pending);
+        assert_eq!(order.identifiers[0].value, "example.com");
+
+        let authorization_id = id(&provider, order.authorizations[0].as_str());
+        let authorization = provider
+            .authorization(
+                &authorization_id,
+                &request(
+                    &provider,
+                    &key,
+                    account,
+                    &ProviderResource::Authorization(authorization_id.clone()),
+                    None,
+                ),
+            )
+            .unwrap();
+        let challenge_id = id(&provider, authorization.challenges[0].url.as_str());
+        let challenge = provider
+            .challenge(
+                &challenge_id,
+                &request(
+                    &provider,
+                    &key,
+                    account,
+                    &ProviderResource::Challenge(challenge_id.clone()),
+                    Some(json!({})),
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(challenge.status, ChallengeStatus::Processing);
+
+        let challenge = provider
+            .retry_challenge(
+                &challenge_id,
+                super::super::Error::new(ErrorType::Connection, "connection refused"),
+            )
+            .unwrap();
+
+        assert_eq!(challenge.status, ChallengeStatus::Processing);
+        assert_eq!(
+            provider
+                .complete_challenge(&challenge_id, Ok(()))
+                .unwrap()
+                .status,
+            ChallengeStatus::Valid
+        );
+
+        let order = provider
+            .order(
+                &order_id,
+                &request(
+                    &provider,
+                    &key,
+                    account,
+                    &ProviderResource::Order(order_id.clone()),
+                    None,
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(order.status, OrderStatus::Ready);
+
+        let order = provider
+            .finalize(
+                &order_id,
+                &request(
+                    &provider,
+                    &key,
+                    account,
+                    &ProviderResource::Finalize(order_id.clone()),
+                    Some(json!({"csr": "MIIB"})),
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(order.status, OrderStatus::Processing);
+        assert!(provider
+            .issue_certificate(&order_id, "not a certificate".to_string())
+            .is_err());
+
+        let order = provider
+            .issue_certificate(&order_id, certificate())
+            .unwrap();
+
+        assert_eq!(order.status, OrderStatus::Valid);
+
+        let certificate_id = id(&provider, order.certificate.unwrap().as_str());
+        let chain = provider
+            .certificate(
+                &certificate_id,
+                &request(
+                    &provider,
+                    &key,
+                    account,
+                    &ProviderResource::Certificate(certificate_id.clone()),
+                    None,
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(chain, certificate());
+
+        provider
+            .revoke_certificate(&revocation(&provider, &key, account))
+            .unwrap();
+
+        assert_eq!(
+            provider
+                .store()
+                .certificate(&certificate_id)
+                .unwrap()
+                .unwrap()
+                .revoked,
+            Some(CertificateRevocationReason::Superseded)
+        );
+        assert_eq!(
+            provider
+                .revoke_certificate(&revocation(&provider, &key, account))
+                .unwrap_err()
+                .type_,
+            ErrorType::AlreadyRevoked
+        );
+    }
+
+    #[test]
+    fn revocation_by_certificate_key() {
+        let provider = provider();
+        let key = signing_key();
+        let account = new_account(&provider, &key);
+        provider
+            .store()
+            .insert_certificate(
+                "certificate",
+                CertificateRecord {
+                    account: id(&provider, account.as_str()),
+                    chain: certificate(),
+                    revoked: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            provider
+                .revoke_certificate(&revocation(&provider, &key, None))
+                .unwrap_err()
+                .type_,
+            ErrorType::Unauthorized
+        );
+        let other = signing_key();
+        let other_account = new_account(&provider, &other);
+
+        assert_eq!(
+            provider
+                .revoke_certificate(&revocation(&provider, &other, Some(&other_account)))
+                .unwrap_err()
+                .type_,
+            ErrorType::Unauthorized
+        );
+
+        let certificate_key =
+            SigningKey::from_pkcs8(JwsAlgorithm::Es256, include_bytes!("testdata/leaf.p8"))
+                .unwrap();
+
+        provider
+            .revoke_certificate(&revocation(&provider, &certificate_key, None))
+            .unwrap();
+    }
+
+    #[test]
+    fn key_rollover() {
+        let provider = provider();
+        let old_key = signing_key();
+        let new_key = signing_key();
+        let account = new_account(&provider, &old_key);
+        let other = signing_key();
+        new_account(&provider, &other);
+
+        let key_change = |new_key: &SigningKey| {
+            let signature = KeyChange::sign(
+                &account,
+                &provider.url(&ProviderResource::KeyChange),
+                provider.new_nonce().unwrap(),
+                &old_key,
+                new_key,
+            )
+            .unwrap();
+            provider.key_change(&SignedRequest::decode(signature).unwrap())
+        };
+
+        assert_eq!(key_change(&other).unwrap_err().type_, ErrorType::Malformed);
+        assert_eq!(key_change(&new_key).unwrap().status, AccountStatus::Valid);
+
+        let only_return_existing = |key: &SigningKey| {
+            provider.new_account(&request(
+                &provider,
+                key,
+                None,
+                &ProviderResource::NewAccount,
+                Some(json!({"onlyReturnExisting": true})),
+            ))
+        };
+
+        assert_eq!(only_return_existing(&new_key).unwrap().0, account);
+        assert_eq!(
+            only_return_existing(&old_key).unwrap_err().type_,
+            ErrorType::AccountDoesNotExist
+        );
+    }
+
+    #[test]
+    fn expiry() {
+        let provider = provider().with_lifetimes(Duration::ZERO, Duration::ZERO);
+        let key = signing_key();
+        let account = new_account(&provider, &key);
+        let account = Some(&account);
+
+        let (order_url, order) = provider
+            .new_order(&request(
+                &provider,
+                &key,
+                account,
+                &ProviderResource::NewOrder,
+                Some(json!({"identifiers": [{"type": "dns", "value": "example.com"}]})),
+            ))
+            .unwrap();
+        let order_id = id(&provider, order_url.as_str());
+        let authorization_id = id(&provider, order.authorizations[0].as_str());
+        let authorization = provider
+            .authorization(
+                &authorization_id,
+                &request(
+                    &provider,
+                    &key,
+                    account,
+                    &ProviderResource::Authorization(authorization_id.clone()),
+                    None,
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(authorization.status, AuthorizationStatus::Expired);
+
+        let order = provider
+            .order(
+                &order_id,
+                &request(
+                    &provider,
+                    &key,
+                    account,
+                    &ProviderResource::Order(order_id.clone()),
+                    None,
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(order.status, OrderStatus::Invalid);
+        assert!(order.error.is_some());
+    }
+
+    #[test]
+    fn rejected_requests() {
+        let provider = provider();
+        let key = signing_key();
+        let account = new_account(&provider, &key);
+        let new_order = |value: &str| {
+            request(
+                &provider,
+                &key,
+                Some(&account),
+                &ProviderResource::NewOrder,
+                Some(json!({"identifiers": [{"type": "dns", "value": value}]})),
+            )
+        };
+
+        assert_eq!(
+            provider
+                .new_order(&new_order("*.*.example.com"))
+                .unwrap_err()
+                .type_,
+            ErrorType::RejectedIdentifier
+        );
+
+        let request = new_order("example.com");
+        provider.new_order(&request).unwrap();
+
+        assert_eq!(
+            provider.new_order(&request).unwrap_err().type_,
+            ErrorType::BadNonce
+        );
+        assert_eq!(
+            provider
+                .new_account(&super::tests::request(
+                    &provider,
+                    &signing_key(),
+                    None,
+                    &ProviderResource::NewAccount,
+                    Some(json!({"contact": ["https://example.com"]})),
+                ))
+                .unwrap_err()
+                .type_,
+            ErrorType::UnsupportedContact
+        );
+    }
+
+    #[test]
+    fn unknown_resources() {
+        let provider = provider();
+        let key = signing_key();
+        let account = new_account(&provider, &key);
+        let account = Some(&account);
+        let unknown = "unknown".to_string();
+        let post_as_get = |resource: ProviderResource| -> SignedRequest<Value> {
+            request(&provider, &key, account, &resource, None)
+        };
+
+        for error in [
+            provider
+                .order(
+                    &unknown,
+                    &post_as_get(ProviderResource::Order(unknown.clone())),
+                )
+                .unwrap_err(),
+            provider
+                .authorization(
+                    &unknown,
+                    &request(
+                        &provider,
+                        &key,
+                        account,
+                        &ProviderResource::Authorization(unknown.clone()),
+                        None,
+                    ),
+                )
+                .unwrap_err(),
+            provider
+                .challenge(
+                    &unknown,
+                    &post_as_get(ProviderResource::Challenge(unknown.clone())),
+                )
+                .unwrap_err(),
+            provider
+                .certificate(
+                    &unknown,
+                    &post_as_get(ProviderResource::Certificate(unknown.clone())),
+                )
+                .unwrap_err(),
+            provider.complete_challenge(&unknown, Ok(())).unwrap_err(),
+        ] {
+            assert_eq!(error.type_, ErrorType::Malformed);
+            assert_eq!(error.status, Some(404));
+        }
+
+        // Other errors, e.g. for a request sent to another URL, do not carry a status code
+        let error = provider
+            .finalize(
+                &unknown,
+                &request(
+                    &provider,
+                    &key,
+                    account,
+                    &ProviderResource::Order(unknown.clone()),
+                    Some(json!({"csr": "MIIB"})),
+                ),
+            )
+            .unwrap_err();
+
+        assert_eq!(error.type_, ErrorType::Unauthorized);
+        assert_eq!(error.status, None);
+    }
+
+    #[test]
+    fn concurrent_requests() {
+        let provider = provider();
+        let key = signing_key();
+        let requests: Vec<SignedRequest<NewAccount>> = (0..8)
+            .map(|_| {
+                request(
+                    &provider,
+                    &key,
+                    None,
+                    &ProviderResource::NewAccount,
+                    Some(json!({})),
+                )
+            })
+            .collect();
+
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let threads: Vec<_> = requests
+                .iter()
+                .map(|request| scope.spawn(|| provider.new_account(request).unwrap()))
+                .collect();
+            threads
+                .into_iter()
+                .map(|thread| thread.join().unwrap())
+                .collect()
+        });
+
+        assert_eq!(results.iter().filter(|(_, _, created)| *created).count(), 1);
+        assert!(results.iter().all(|(url, _, _)| *url == results[0].0));
+    }
+
+    #[test]
+    fn deactivated_account() {
+        let provider = provider();
+        let key = signing_key();
+        let account = new_account(&provider, &key);
+        let account_id = id(&provider, account.as_str());
+
+        let deactivated = provider
+            .account(
+                &account_id,
+                &request(
+                    &provider,
+                    &key,
+                    Some(&account),
+                    &ProviderResource::Account(account_id.clone()),
+                    Some(json!({"status": "deactivated"})),
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(deactivated.status, AccountStatus::Deactivated);
+
+        let error = provider
+            .new_account(&request(
+                &provider,
+                &key,
+                None,
+                &ProviderResource::NewAccount,
+                Some(json!({"onlyReturnExisting": true})),
+            ))
+            .unwrap_err();
+
+        assert_eq!(error.type_, ErrorType::Unauthorized);
+        assert_eq!(error.detail.as_deref(), Some("account is deactivated"));
+        assert_eq!(
+            provider
+                .new_order(&request(
+                    &provider,
+                    &key,
+                    Some(&account),
+                    &ProviderResource::NewOrder,
+                    Some(json!({"identifiers": [{"type": "dns", "value": "example.com"}]})),
+                ))
+                .unwrap_err()
+                .type_,
+            ErrorType::Unauthorized
+        );
+    }
+}
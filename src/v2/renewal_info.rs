@@ -0,0 +1,119 @@
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// Defines an ACME renewal information resource.
+///
+/// For more information, refer to [RFC 9773 § 4.2](https://datatracker.ietf.org/doc/html/rfc9773#section-4.2)
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct RenewalInfo {
+    /// Time window during which the certificate should be renewed
+    #[cfg_attr(feature = "json", serde(rename = "suggestedWindow"))]
+    pub suggested_window: SuggestedWindow,
+    /// URL of a page explaining why the suggested renewal window was chosen
+    #[cfg_attr(feature = "json", serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(feature = "json", serde(rename = "explanationURL"))]
+    pub explanation_url: Option<String>,
+}
+
+#[cfg(feature = "json")]
+impl RenewalInfo {
+    /// Deserializes a RenewalInfo object from a JSON str
+    pub fn from_str(s: &str) -> Result<RenewalInfo, serde_json::error::Error> {
+        serde_json::from_str(s)
+    }
+
+    /// Serializes a RenewalInfo object to a JSON String
+    pub fn to_string(&self) -> Result<String, serde_json::error::Error> {
+        serde_json::to_string(self)
+    }
+}
+
+#[cfg(feature = "base64")]
+impl RenewalInfo {
+    /// Builds the renewal information certificate identifier of a DER-encoded certificate, i.e. the
+    /// base64url-encoded authority key identifier and serial number, joined by a `.`
+    ///
+    /// Returns `None` if the certificate cannot be parsed or has no authority key identifier.
+    ///
+    /// For more information, refer to [RFC 9773 § 4.1](https://datatracker.ietf.org/doc/html/rfc9773#section-4.1)
+    pub fn certificate_identifier(certificate: &[u8]) -> Option<String> {
+        use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+        // id-ce-authorityKeyIdentifier (2.5.29.35)
+        let aki = super::der::extension(certificate, &[0x55, 0x1d, 0x23])?;
+        let (aki, _) = super::der::expect(aki, super::der::TAG_SEQUENCE)?;
+        let key_identifier = match super::der::read(aki)? {
+            (0x80, key_identifier, _) => key_identifier,
+            _ => return None,
+        };
+        let serial = super::der::serial_number(certificate)?;
+
+        Some(format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(key_identifier),
+            URL_SAFE_NO_PAD.encode(serial)
+        ))
+    }
+}
+
+/// Defines the suggested renewal window of a renewal information resource.
+///
+/// For more information, refer to [RFC 9773 § 4.2](https://datatracker.ietf.org/doc/html/rfc9773#section-4.2)
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct SuggestedWindow {
+    /// Start of the renewal window
+    pub start: super::Timestamp,
+    /// End of the renewal window
+    pub end: super::Timestamp,
+}
+
+#[cfg(all(test, any(feature = "json", feature = "crypto")))]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_round_trip() {
+        let json = r#"{"suggestedWindow":{"start":"2025-01-02T04:00:00Z","end":"2025-01-03T04:00:00Z"},"explanationURL":"https://acme.example.com/docs/ari"}"#;
+        let renewal_info = RenewalInfo::from_str(json).unwrap();
+
+        assert_eq!(
+            renewal_info.suggested_window.start.as_str(),
+            "2025-01-02T04:00:00Z"
+        );
+        assert_eq!(
+            renewal_info.suggested_window.end.as_str(),
+            "2025-01-03T04:00:00Z"
+        );
+        assert_eq!(
+            renewal_info.explanation_url.as_deref(),
+            Some("https://acme.example.com/docs/ari")
+        );
+        assert_eq!(renewal_info.to_string().unwrap(), json);
+
+        let renewal_info = RenewalInfo::from_str(
+            r#"{"suggestedWindow":{"start":"2025-01-02T04:00:00Z","end":"2025-01-03T04:00:00Z"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(renewal_info.explanation_url, None);
+        assert!(!renewal_info.to_string().unwrap().contains("explanationURL"));
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn certificate_identifier() {
+        let chain =
+            crate::v2::CertificateChain::from_pem(include_str!("testdata/ari.pem")).unwrap();
+
+        // RFC 9773 § 4.1 example
+        assert_eq!(
+            RenewalInfo::certificate_identifier(chain.leaf()).unwrap(),
+            "aYhba4dGQEHhs3uEe6CuLN4ByNQ.AIdlQyE"
+        );
+
+        assert_eq!(RenewalInfo::certificate_identifier(&[0x30, 0x00]), None);
+    }
+}
@@ -0,0 +1,215 @@
+#[cfg(feature = "json")]
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Defines a newtype for a kind of resource URL, so that URLs of different kinds cannot be mixed up
+///
+/// URLs hold the string sent over the wire whichever features are enabled. With the `url` feature
+/// enabled, they are validated on deserialization and can be parsed into `url::Url` values.
+macro_rules! resource_url {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "json", derive(Serialize))]
+        #[cfg_attr(feature = "json", serde(transparent))]
+        pub struct $name(String);
+
+        impl $name {
+            /// Creates a URL from a string, which is not validated
+            pub fn new(url: impl Into<String>) -> $name {
+                $name(url.into())
+            }
+
+            /// Returns the URL as a string slice
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        #[cfg(feature = "url")]
+        impl $name {
+            /// Creates a URL from a string, which must be a valid absolute URL
+            pub fn parse(url: &str) -> Result<$name, url::ParseError> {
+                url::Url::parse(url)?;
+                Ok($name(url.to_string()))
+            }
+
+            /// Parses the URL into a `url::Url` value
+            pub fn url(&self) -> Result<url::Url, url::ParseError> {
+                url::Url::parse(&self.0)
+            }
+        }
+
+        #[cfg(feature = "url")]
+        impl From<url::Url> for $name {
+            fn from(url: url::Url) -> $name {
+                $name(url.into())
+            }
+        }
+
+        #[cfg(feature = "json")]
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$name, D::Error> {
+                let url = String::deserialize(deserializer)?;
+                #[cfg(feature = "url")]
+                url::Url::parse(&url).map_err(serde::de::Error::custom)?;
+
+                Ok($name(url))
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                self.as_str()
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+resource_url! {
+    /// URL of an ACME account resource
+    ///
+    /// For more information, refer to [RFC 8555 § 7.3](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3)
+    AccountUrl
+}
+
+resource_url! {
+    /// URL of an ACME order resource
+    ///
+    /// For more information, refer to [RFC 8555 § 7.4](https://datatracker.ietf.org/doc/html/rfc8555#section-7.4)
+    OrderUrl
+}
+
+resource_url! {
+    /// URL of an ACME authorization resource
+    ///
+    /// For more information, refer to [RFC 8555 § 7.5](https://datatracker.ietf.org/doc/html/rfc8555#section-7.5)
+    AuthorizationUrl
+}
+
+resource_url! {
+    /// URL of an ACME challenge resource
+    ///
+    /// For more information, refer to [RFC 8555 § 7.5.1](https://datatracker.ietf.org/doc/html/rfc8555#section-7.5.1)
+    ChallengeUrl
+}
+
+resource_url! {
+    /// URL used to finalize an ACME order
+    ///
+    /// For more information, refer to [RFC 8555 § 7.4](https://datatracker.ietf.org/doc/html/rfc8555#section-7.4)
+    FinalizeUrl
+}
+
+resource_url! {
+    /// URL from which an issued certificate can be downloaded
+    ///
+    /// For more information, refer to [RFC 8555 § 7.4.2](https://datatracker.ietf.org/doc/html/rfc8555#section-7.4.2)
+    CertificateUrl
+}
+
+resource_url! {
+    /// URL from which the list of orders of an ACME account can be retrieved
+    ///
+    /// For more information, refer to [RFC 8555 § 7.1.2.1](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.2.1)
+    AccountOrdersUrl
+}
+
+resource_url! {
+    /// Directory URL from which new nonces can be retrieved
+    ///
+    /// For more information, refer to [RFC 8555 § 7.2](https://datatracker.ietf.org/doc/html/rfc8555#section-7.2)
+    NewNonceUrl
+}
+
+resource_url! {
+    /// Directory URL used to create ACME accounts
+    ///
+    /// For more information, refer to [RFC 8555 § 7.3](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3)
+    NewAccountUrl
+}
+
+resource_url! {
+    /// Directory URL used to create ACME orders
+    ///
+    /// For more information, refer to [RFC 8555 § 7.4](https://datatracker.ietf.org/doc/html/rfc8555#section-7.4)
+    NewOrderUrl
+}
+
+resource_url! {
+    /// Directory URL used to pre-authorize identifiers
+    ///
+    /// For more information, refer to [RFC 8555 § 7.4.1](https://datatracker.ietf.org/doc/html/rfc8555#section-7.4.1)
+    NewAuthorizationUrl
+}
+
+resource_url! {
+    /// Directory URL used to revoke certificates
+    ///
+    /// For more information, refer to [RFC 8555 § 7.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.6)
+    RevokeCertificateUrl
+}
+
+resource_url! {
+    /// Directory URL used to change the key of an ACME account
+    ///
+    /// For more information, refer to [RFC 8555 § 7.3.5](https://datatracker.ietf.org/doc/html/rfc8555#section-7.3.5)
+    KeyChangeUrl
+}
+
+resource_url! {
+    /// Directory URL from which renewal information can be retrieved
+    ///
+    /// For more information, refer to [RFC 9773 § 4](https://datatracker.ietf.org/doc/html/rfc9773#section-4)
+    RenewalInfoUrl
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transparent_serialization() {
+        let url = OrderUrl::new("https://example.com/acme/order/TOlocE8rfgo");
+        let json = serde_json::to_string(&url).unwrap();
+        assert_eq!(json, r#""https://example.com/acme/order/TOlocE8rfgo""#);
+        assert_eq!(serde_json::from_str::<OrderUrl>(&json).unwrap(), url);
+
+        // The URL is kept exactly as sent, not normalized
+        let url: FinalizeUrl = serde_json::from_str(r#""HTTPS://Example.com""#).unwrap();
+        assert_eq!(url.as_str(), "HTTPS://Example.com");
+        assert_eq!(
+            serde_json::to_string(&url).unwrap(),
+            r#""HTTPS://Example.com""#
+        );
+
+        assert!(serde_json::from_str::<OrderUrl>("1").is_err());
+    }
+
+    #[cfg(feature = "url")]
+    #[test]
+    fn validation() {
+        for url in ["", "/acme/order/1", "not a url", "https://exa mple.com"] {
+            let json = serde_json::to_string(url).unwrap();
+            assert!(serde_json::from_str::<OrderUrl>(&json).is_err(), "{}", url);
+            assert!(OrderUrl::parse(url).is_err(), "{}", url);
+            assert!(OrderUrl::new(url).url().is_err(), "{}", url);
+        }
+
+        let url = AuthorizationUrl::parse("https://example.com/acme/authz/PAniVnsZcis").unwrap();
+        assert_eq!(url.url().unwrap().path(), "/acme/authz/PAniVnsZcis");
+        assert_eq!(AuthorizationUrl::from(url.url().unwrap()), url);
+    }
+
+    #[cfg(not(feature = "url"))]
+    #[test]
+    fn validation() {
+        let url: OrderUrl = serde_json::from_str(r#""/acme/order/1""#).unwrap();
+        assert_eq!(url.as_str(), "/acme/order/1");
+    }
+}
@@ -0,0 +1,278 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::de::DeserializeOwned;
+
+use super::{JsonWebKey, JsonWebSignature, JsonWebSignatureProtected};
+
+/// Defines a decoded ACME request, i.e. a JSON web signature whose protected header and payload
+/// have been decoded and type-checked.
+///
+/// The signature itself is not verified by decoding; refer to `SignedRequest::verify` (requires
+/// the `crypto` feature).
+///
+/// For more information, refer to [RFC 8555 § 6.2](https://datatracker.ietf.org/doc/html/rfc8555#section-6.2)
+#[derive(Clone, Debug)]
+pub struct SignedRequest<T> {
+    /// Decoded protected header
+    pub protected: JsonWebSignatureProtected,
+    /// Decoded payload, or `None` for POST-as-GET requests
+    pub payload: Option<T>,
+    /// JSON web signature the request was decoded from
+    pub signature: JsonWebSignature,
+}
+
+/// Defines the key used to sign an ACME request
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequestKey<'a> {
+    /// Key embedded in the request (`jwk`), used for new account and revocation requests
+    JsonWebKey(&'a JsonWebKey),
+    /// URL of the account whose key signed the request (`kid`)
+    KeyId(&'a str),
+}
+
+impl<T: DeserializeOwned> SignedRequest<T> {
+    /// Decodes and type-checks a JSON web signature
+    ///
+    /// An empty payload is decoded as a POST-as-GET request. Exactly one of the `jwk` and `kid`
+    /// protected header members must be present.
+    pub fn decode(signature: JsonWebSignature) -> Result<SignedRequest<T>, SignedRequestError> {
+        let protected = decode(&signature.protected, "protected")?;
+        let protected: JsonWebSignatureProtected = serde_json::from_slice(&protected)
+            .map_err(|e| SignedRequestError::Protected(e.to_string()))?;
+
+        match (&protected.json_web_key, &protected.key_id) {
+            (Some(_), None) | (None, Some(_)) => (),
+            _ => return Err(SignedRequestError::KeyIdentification),
+        }
+
+        let payload = match signature.payload.is_empty() {
+            true => None,
+            false => {
+                let payload = decode(&signature.payload, "payload")?;
+                Some(
+                    serde_json::from_slice(&payload)
+                        .map_err(|e| SignedRequestError::Payload(e.to_string()))?,
+                )
+            }
+        };
+
+        Ok(SignedRequest {
+            protected,
+            payload,
+            signature,
+        })
+    }
+
+    /// Deserializes a JSON web signature from a JSON str, then decodes and type-checks it
+    pub fn from_str(s: &str) -> Result<SignedRequest<T>, SignedRequestError> {
+        let signature = JsonWebSignature::from_str(s)
+            .map_err(|e| SignedRequestError::Signature(e.to_string()))?;
+
+        SignedRequest::decode(signature)
+    }
+}
+
+impl<T> SignedRequest<T> {
+    /// Returns true if the request is a POST-as-GET request, i.e. has an empty payload
+    ///
+    /// For more information, refer to [RFC 8555 § 6.3](https://datatracker.ietf.org/doc/html/rfc8555#section-6.3)
+    pub fn is_post_as_get(&self) -> bool {
+        self.payload.is_none()
+    }
+
+    /// Returns the payload, or an error if the request is a POST-as-GET request
+    pub fn require_payload(&self) -> Result<&T, SignedRequestError> {
+        self.payload
+            .as_ref()
+            .ok_or(SignedRequestError::MissingPayload)
+    }
+
+    /// Returns the key used to sign the request
+    pub fn key(&self) -> Option<RequestKey<'_>> {
+        match (&self.protected.json_web_key, &self.protected.key_id) {
+            (Some(key), _) => Some(RequestKey::JsonWebKey(key)),
+            (None, Some(key_id)) => Some(RequestKey::KeyId(key_id)),
+            (None, None) => None,
+        }
+    }
+
+    /// Verifies the request signature using the given public key
+    #[cfg(feature = "crypto")]
+    pub fn verify(&self, key: &JsonWebKey) -> Result<(), super::JwsError> {
+        self.signature.verify(key).map(|_| ())
+    }
+}
+
+fn decode(value: &str, member: &'static str) -> Result<Vec<u8>, SignedRequestError> {
+    URL_SAFE_NO_PAD
+        .decode(value)
+        .map_err(|_| SignedRequestError::Base64(member))
+}
+
+/// Errors encountered while decoding an ACME request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignedRequestError {
+    /// The request body is not a flattened JSON web signature
+    Signature(String),
+    /// A member is not valid unpadded base64url
+    Base64(&'static str),
+    /// The protected header is invalid
+    Protected(String),
+    /// The protected header does not contain exactly one of `jwk` and `kid`
+    KeyIdentification,
+    /// The payload is not valid for the request
+    Payload(String),
+    /// The request requires a payload, but is a POST-as-GET request
+    MissingPayload,
+}
+
+impl SignedRequestError {
+    /// Returns the ACME error type an ACME provider should return for this error
+    pub fn error_type(&self) -> super::ErrorType {
+        super::ErrorType::Malformed
+    }
+}
+
+impl std::fmt::Display for SignedRequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignedRequestError::Signature(e) => write!(f, "request is not a valid JWS: {}", e),
+            SignedRequestError::Base64(member) => {
+                write!(f, "JWS {} is not valid base64url", member)
+            }
+            SignedRequestError::Protected(e) => write!(f, "JWS protected header is invalid: {}", e),
+            SignedRequestError::KeyIdentification => write!(
+                f,
+                "JWS protected header must contain exactly one of jwk and kid"
+            ),
+            SignedRequestError::Payload(e) => write!(f, "JWS payload is invalid: {}", e),
+            SignedRequestError::MissingPayload => write!(f, "JWS payload must not be empty"),
+        }
+    }
+}
+
+impl std::error::Error for SignedRequestError {}
+
+impl From<SignedRequestError> for super::Error {
+    fn from(error: SignedRequestError) -> super::Error {
+        super::Error::new(error.error_type(), error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2::{Error, ErrorType, NewOrder};
+
+    const JWK: &str = r#""jwk":{"kty":"EC","crv":"P-256","x":"eA","y":"eQ"}"#;
+    const KID: &str = r#""kid":"https://example.com/acme/acct/1""#;
+    const PAYLOAD: &str = r#"{"identifiers":[{"type":"dns","value":"example.com"}]}"#;
+
+    fn signature(members: &[&str], payload: &str) -> JsonWebSignature {
+        let protected = format!(
+            r#"{{"alg":"ES256","nonce":"nonce","url":"https://example.com/acme/new-order",{}}}"#,
+            members.join(",")
+        );
+
+        JsonWebSignature {
+            protected: URL_SAFE_NO_PAD.encode(protected),
+            payload: URL_SAFE_NO_PAD.encode(payload),
+            signature: "c2lnbmF0dXJl".to_string(),
+        }
+    }
+
+    fn decode_error(signature: JsonWebSignature) -> SignedRequestError {
+        let error = SignedRequest::<NewOrder>::decode(signature).unwrap_err();
+        assert_eq!(error.error_type(), ErrorType::Malformed);
+        assert_eq!(Error::from(error.clone()).type_, ErrorType::Malformed);
+        error
+    }
+
+    #[test]
+    fn decode() {
+        let request = SignedRequest::<NewOrder>::decode(signature(&[KID], PAYLOAD)).unwrap();
+
+        assert!(!request.is_post_as_get());
+        assert_eq!(request.require_payload().unwrap().identifiers.len(), 1);
+        assert_eq!(
+            request.key(),
+            Some(RequestKey::KeyId("https://example.com/acme/acct/1"))
+        );
+
+        let request = SignedRequest::<NewOrder>::decode(signature(&[JWK], PAYLOAD)).unwrap();
+
+        assert!(matches!(request.key(), Some(RequestKey::JsonWebKey(_))));
+    }
+
+    #[test]
+    fn post_as_get() {
+        let request = SignedRequest::<NewOrder>::decode(signature(&[KID], "")).unwrap();
+
+        assert!(request.is_post_as_get());
+        assert_eq!(
+            request.require_payload().unwrap_err(),
+            SignedRequestError::MissingPayload
+        );
+        assert_eq!(
+            SignedRequestError::MissingPayload.error_type(),
+            ErrorType::Malformed
+        );
+    }
+
+    #[test]
+    fn key_identification() {
+        assert_eq!(
+            decode_error(signature(&[JWK, KID], PAYLOAD)),
+            SignedRequestError::KeyIdentification
+        );
+        assert_eq!(
+            decode_error(signature(&[r#""typ":"JWT""#], PAYLOAD)),
+            SignedRequestError::KeyIdentification
+        );
+    }
+
+    #[test]
+    fn invalid_base64() {
+        let mut invalid = signature(&[KID], PAYLOAD);
+        invalid.protected.push('=');
+        assert_eq!(
+            decode_error(invalid),
+            SignedRequestError::Base64("protected")
+        );
+
+        let mut invalid = signature(&[KID], PAYLOAD);
+        invalid.payload.insert(0, '+');
+        assert_eq!(decode_error(invalid), SignedRequestError::Base64("payload"));
+    }
+
+    #[test]
+    fn invalid_protected() {
+        let mut invalid = signature(&[KID], PAYLOAD);
+        invalid.protected = URL_SAFE_NO_PAD.encode(r#"{"kid":"https://example.com"}"#);
+
+        assert!(matches!(
+            decode_error(invalid),
+            SignedRequestError::Protected(_)
+        ));
+    }
+
+    #[test]
+    fn invalid_payload() {
+        for payload in [r#"{"identifiers":"example.com"}"#, "[]", "not json"] {
+            assert!(matches!(
+                decode_error(signature(&[KID], payload)),
+                SignedRequestError::Payload(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn from_str() {
+        assert!(matches!(
+            SignedRequest::<NewOrder>::from_str("{}").unwrap_err(),
+            SignedRequestError::Signature(_)
+        ));
+
+        let signature = signature(&[KID], PAYLOAD).to_string().unwrap();
+        assert!(SignedRequest::<NewOrder>::from_str(&signature).is_ok());
+    }
+}
@@ -0,0 +1,210 @@
+use super::{
+    AccountEvent, AccountStatus, AuthorizationEvent, AuthorizationStatus, ChallengeEvent,
+    ChallengeStatus, OrderEvent, OrderStatus,
+};
+
+/// Errors encountered while transitioning a resource status, holding the current status and the
+/// event the RFC 8555 state diagram does not allow in that status
+///
+/// For more information, refer to [RFC 8555 § 7.1.6](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1.6)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatusTransitionError {
+    Account(AccountStatus, AccountEvent),
+    Authorization(AuthorizationStatus, AuthorizationEvent),
+    Challenge(ChallengeStatus, ChallengeEvent),
+    Order(OrderStatus, OrderEvent),
+}
+
+impl StatusTransitionError {
+    /// Returns the ACME error type an ACME provider should return for this error
+    ///
+    /// Finalizing an order which is not `ready` is reported as `orderNotReady`.
+    pub fn error_type(&self) -> super::ErrorType {
+        match self {
+            StatusTransitionError::Order(_, OrderEvent::Finalize) => {
+                super::ErrorType::OrderNotReady
+            }
+            _ => super::ErrorType::Malformed,
+        }
+    }
+}
+
+impl std::fmt::Display for StatusTransitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (resource, status, event) = match self {
+            StatusTransitionError::Account(status, event) => {
+                ("account", status.as_str(), event.as_str())
+            }
+            StatusTransitionError::Authorization(status, event) => {
+                ("authorization", status.as_str(), event.as_str())
+            }
+            StatusTransitionError::Challenge(status, event) => {
+                ("challenge", status.as_str(), event.as_str())
+            }
+            StatusTransitionError::Order(status, event) => {
+                ("order", status.as_str(), event.as_str())
+            }
+        };
+
+        write!(
+            f,
+            "{} status {} does not allow event: {}",
+            resource, status, event
+        )
+    }
+}
+
+impl std::error::Error for StatusTransitionError {}
+
+impl From<StatusTransitionError> for super::Error {
+    fn from(error: StatusTransitionError) -> super::Error {
+        super::Error::new(error.error_type(), error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2::ErrorType;
+
+    /// Checks every status and event against the edges allowed by the state diagram: allowed
+    /// edges must lead to their target status, any other event must be rejected, and a status
+    /// must be terminal if and only if no edge leaves it
+    fn assert_transitions<S, E>(
+        statuses: &[S],
+        events: &[E],
+        allowed: &[(S, E, S)],
+        transition: fn(S, E) -> Result<S, StatusTransitionError>,
+        is_terminal: fn(&S) -> bool,
+        error: fn(S, E) -> StatusTransitionError,
+    ) where
+        S: Copy + PartialEq + std::fmt::Debug,
+        E: Copy + PartialEq,
+    {
+        for &status in statuses {
+            for &event in events {
+                let expected = allowed
+                    .iter()
+                    .find(|(from, on, _)| *from == status && *on == event)
+                    .map(|(_, _, to)| *to)
+                    .ok_or(error(status, event));
+
+                assert_eq!(transition(status, event), expected);
+            }
+
+            assert_eq!(
+                is_terminal(&status),
+                !allowed.iter().any(|t| t.0 == status),
+                "{:?}",
+                status
+            );
+        }
+    }
+
+    #[test]
+    fn account_transitions() {
+        use AccountEvent::*;
+        use AccountStatus::*;
+
+        let allowed = [(Valid, Deactivate, Deactivated), (Valid, Revoke, Revoked)];
+
+        assert_transitions(
+            &[Valid, Deactivated, Revoked],
+            &[Deactivate, Revoke],
+            &allowed,
+            AccountStatus::transition,
+            AccountStatus::is_terminal,
+            StatusTransitionError::Account,
+        );
+    }
+
+    #[test]
+    fn authorization_transitions() {
+        use AuthorizationEvent::*;
+        use AuthorizationStatus::*;
+
+        let allowed = [
+            (Pending, ChallengeValid, Valid),
+            (Pending, Fail, Invalid),
+            (Pending, Expire, Expired),
+            (Pending, Deactivate, Deactivated),
+            (Pending, Revoke, Revoked),
+            (Valid, Expire, Expired),
+            (Valid, Deactivate, Deactivated),
+            (Valid, Revoke, Revoked),
+        ];
+
+        assert_transitions(
+            &[Pending, Expired, Deactivated, Revoked, Valid, Invalid],
+            &[ChallengeValid, Fail, Expire, Deactivate, Revoke],
+            &allowed,
+            AuthorizationStatus::transition,
+            AuthorizationStatus::is_terminal,
+            StatusTransitionError::Authorization,
+        );
+    }
+
+    #[test]
+    fn challenge_transitions() {
+        use ChallengeEvent::*;
+        use ChallengeStatus::*;
+
+        let allowed = [
+            (Pending, Respond, Processing),
+            (Processing, Retry, Processing),
+            (Processing, Validate, Valid),
+            (Processing, Fail, Invalid),
+        ];
+
+        assert_transitions(
+            &[Pending, Processing, Valid, Invalid],
+            &[Respond, Retry, Validate, Fail],
+            &allowed,
+            ChallengeStatus::transition,
+            ChallengeStatus::is_terminal,
+            StatusTransitionError::Challenge,
+        );
+    }
+
+    #[test]
+    fn order_transitions() {
+        use OrderEvent::*;
+        use OrderStatus::*;
+
+        let allowed = [
+            (Pending, AuthorizationsValid, Ready),
+            (Pending, Fail, Invalid),
+            (Ready, Finalize, Processing),
+            (Ready, Fail, Invalid),
+            (Processing, CertificateIssued, Valid),
+            (Processing, Fail, Invalid),
+        ];
+
+        assert_transitions(
+            &[Pending, Ready, Processing, Valid, Invalid],
+            &[AuthorizationsValid, Finalize, CertificateIssued, Fail],
+            &allowed,
+            OrderStatus::transition,
+            OrderStatus::is_terminal,
+            StatusTransitionError::Order,
+        );
+    }
+
+    #[test]
+    fn error_types() {
+        assert_eq!(
+            StatusTransitionError::Order(OrderStatus::Pending, OrderEvent::Finalize).error_type(),
+            ErrorType::OrderNotReady
+        );
+        assert_eq!(
+            StatusTransitionError::Challenge(ChallengeStatus::Valid, ChallengeEvent::Retry)
+                .error_type(),
+            ErrorType::Malformed
+        );
+        assert_eq!(
+            StatusTransitionError::Challenge(ChallengeStatus::Pending, ChallengeEvent::Retry)
+                .to_string(),
+            "challenge status pending does not allow event: retry"
+        );
+    }
+}
@@ -0,0 +1,570 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use time::OffsetDateTime;
+
+use super::{
+    Account, AuthorizationStatus, CertificateChain, CertificateRevocationReason, Challenge,
+    Identifier, JsonWebKey, Order,
+};
+
+/// Defines a stored ACME account, along with the key which signs its requests
+#[derive(Clone, Debug)]
+pub struct AccountRecord {
+    /// Account key
+    pub key: JsonWebKey,
+    /// Account resource
+    pub account: Account,
+}
+
+/// Defines a stored ACME order
+#[derive(Clone, Debug)]
+pub struct OrderRecord {
+    /// Identifier of the account which created the order
+    pub account: String,
+    /// Order resource
+    pub order: Order,
+    /// Identifiers of the order's authorizations
+    pub authorizations: Vec<String>,
+    /// DER-encoded CSR, once the order has been finalized
+    pub certificate_signing_request: Option<Vec<u8>>,
+}
+
+/// Defines a stored ACME authorization, whose challenges are stored separately
+#[derive(Clone, Debug)]
+pub struct AuthorizationRecord {
+    /// Identifier of the account which owns the authorization
+    pub account: String,
+    /// Authorization identifier
+    pub identifier: Identifier,
+    /// Authorization status
+    pub status: AuthorizationStatus,
+    /// Authorization expiration time
+    pub expires: Option<OffsetDateTime>,
+    /// Present and true for authorizations for a domain name containing a wildcard
+    pub wildcard: Option<bool>,
+    /// Identifiers of the authorization's challenges
+    pub challenges: Vec<String>,
+}
+
+/// Defines a stored ACME challenge
+#[derive(Clone, Debug)]
+pub struct ChallengeRecord {
+    /// Identifier of the authorization the challenge belongs to
+    pub authorization: String,
+    /// Challenge resource
+    pub challenge: Challenge,
+}
+
+/// Defines a stored certificate
+#[derive(Clone, Debug)]
+pub struct CertificateRecord {
+    /// Identifier of the account which ordered the certificate
+    pub account: String,
+    /// PEM certificate chain
+    pub chain: String,
+    /// Revocation reason, once the certificate has been revoked
+    pub revoked: Option<CertificateRevocationReason>,
+}
+
+/// Defines the storage backend of an [`AcmeProvider`](super::AcmeProvider)
+///
+/// Records are keyed by the opaque identifiers the provider generates for each resource. Errors
+/// are returned as ACME errors (typically `serverInternal`) and passed on to the ACME client.
+///
+/// Records are modified by `update_*` methods, which the store must apply atomically (e.g. under a
+/// lock or in a transaction): `update` is called on the current record, and the result is stored
+/// unless `update` returns an error, which is then returned. Stores retrying a conflicting
+/// transaction may call `update` again on the latest record.
+pub trait ProviderStore {
+    /// Returns the account with the given identifier
+    fn account(&self, id: &str) -> Result<Option<AccountRecord>, Box<super::Error>>;
+    /// Returns the identifier of the account with the given key thumbprint
+    fn account_by_thumbprint(&self, thumbprint: &str) -> Result<Option<String>, Box<super::Error>>;
+    /// Inserts the account with the given identifier, unless an account already holds its key
+    ///
+    /// Checking and claiming the key thumbprint must be atomic. Returns the identifier of the
+    /// account holding the key, in which case nothing is inserted.
+    fn insert_account(
+        &self,
+        id: &str,
+        record: AccountRecord,
+    ) -> Result<Option<String>, Box<super::Error>>;
+    /// Updates the account with the given identifier, returning the updated record or `None` if
+    /// the account does not exist
+    fn update_account(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut Account) -> Result<(), Box<super::Error>>,
+    ) -> Result<Option<AccountRecord>, Box<super::Error>>;
+    /// Replaces the key of the account with the given identifier, unless an account (including
+    /// this one) already holds the new key, returning false in that case
+    ///
+    /// Checking and claiming the key thumbprint must be atomic.
+    fn replace_account_key(&self, id: &str, key: JsonWebKey) -> Result<bool, Box<super::Error>>;
+
+    /// Returns the order with the given identifier
+    fn order(&self, id: &str) -> Result<Option<OrderRecord>, Box<super::Error>>;
+    /// Returns the identifiers of the account's orders, oldest first
+    fn account_orders(&self, account: &str) -> Result<Vec<String>, Box<super::Error>>;
+    /// Inserts the order with the given identifier
+    fn insert_order(&self, id: &str, record: OrderRecord) -> Result<(), Box<super::Error>>;
+    /// Updates the order with the given identifier, returning the updated record or `None` if the
+    /// order does not exist
+    fn update_order(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut OrderRecord) -> Result<(), Box<super::Error>>,
+    ) -> Result<Option<OrderRecord>, Box<super::Error>>;
+
+    /// Returns the authorization with the given identifier
+    fn authorization(&self, id: &str) -> Result<Option<AuthorizationRecord>, Box<super::Error>>;
+    /// Inserts the authorization with the given identifier
+    fn insert_authorization(
+        &self,
+        id: &str,
+        record: AuthorizationRecord,
+    ) -> Result<(), Box<super::Error>>;
+    /// Updates the authorization with the given identifier, returning the updated record or
+    /// `None` if the authorization does not exist
+    fn update_authorization(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut AuthorizationRecord) -> Result<(), Box<super::Error>>,
+    ) -> Result<Option<AuthorizationRecord>, Box<super::Error>>;
+
+    /// Returns the challenge with the given identifier
+    fn challenge(&self, id: &str) -> Result<Option<ChallengeRecord>, Box<super::Error>>;
+    /// Inserts the challenge with the given identifier
+    fn insert_challenge(&self, id: &str, record: ChallengeRecord) -> Result<(), Box<super::Error>>;
+    /// Updates the challenge with the given identifier, returning the updated record or `None` if
+    /// the challenge does not exist
+    fn update_challenge(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut ChallengeRecord) -> Result<(), Box<super::Error>>,
+    ) -> Result<Option<ChallengeRecord>, Box<super::Error>>;
+
+    /// Returns the certificate with the given identifier
+    fn certificate(&self, id: &str) -> Result<Option<CertificateRecord>, Box<super::Error>>;
+    /// Returns the identifier of the certificate whose chain starts with the given DER-encoded
+    /// end-entity certificate
+    fn certificate_by_leaf(&self, leaf: &[u8]) -> Result<Option<String>, Box<super::Error>>;
+    /// Inserts the certificate with the given identifier
+    fn insert_certificate(
+        &self,
+        id: &str,
+        record: CertificateRecord,
+    ) -> Result<(), Box<super::Error>>;
+    /// Updates the certificate with the given identifier, returning the updated record or `None`
+    /// if the certificate does not exist
+    fn update_certificate(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut CertificateRecord) -> Result<(), Box<super::Error>>,
+    ) -> Result<Option<CertificateRecord>, Box<super::Error>>;
+}
+
+/// Defines an in-memory [`ProviderStore`], e.g. for testing
+///
+/// All the records are held behind a single lock, which each operation holds until it returns.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    tables: Mutex<Tables>,
+}
+
+#[derive(Debug, Default)]
+struct Tables {
+    accounts: HashMap<String, AccountRecord>,
+    /// Account identifiers, by key thumbprint
+    thumbprints: HashMap<String, String>,
+    orders: HashMap<String, OrderRecord>,
+    /// Order identifiers, oldest first, by account identifier
+    account_orders: HashMap<String, Vec<String>>,
+    authorizations: HashMap<String, AuthorizationRecord>,
+    challenges: HashMap<String, ChallengeRecord>,
+    certificates: HashMap<String, CertificateRecord>,
+    /// Certificate identifiers, by DER-encoded end-entity certificate
+    leaves: HashMap<Vec<u8>, String>,
+}
+
+impl MemoryStore {
+    /// Creates an empty store
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+
+    fn tables(&self) -> std::sync::MutexGuard<'_, Tables> {
+        self.tables.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Returns the DER-encoded end-entity certificate of a PEM certificate chain
+fn leaf(chain: &str) -> Option<Vec<u8>> {
+    CertificateChain::from_pem(chain)
+        .ok()
+        .map(|chain| chain.leaf().to_vec())
+}
+
+/// Applies an update to a copy of the record, replacing the record only if the update succeeds
+fn update<T: Clone>(
+    records: &mut HashMap<String, T>,
+    id: &str,
+    update: &mut dyn FnMut(&mut T) -> Result<(), Box<super::Error>>,
+) -> Result<Option<T>, Box<super::Error>> {
+    let Some(record) = records.get_mut(id) else {
+        return Ok(None);
+    };
+
+    let mut updated = record.clone();
+    update(&mut updated)?;
+    *record = updated.clone();
+
+    Ok(Some(updated))
+}
+
+impl ProviderStore for MemoryStore {
+    fn account(&self, id: &str) -> Result<Option<AccountRecord>, Box<super::Error>> {
+        Ok(self.tables().accounts.get(id).cloned())
+    }
+
+    fn account_by_thumbprint(&self, thumbprint: &str) -> Result<Option<String>, Box<super::Error>> {
+        Ok(self.tables().thumbprints.get(thumbprint).cloned())
+    }
+
+    fn insert_account(
+        &self,
+        id: &str,
+        record: AccountRecord,
+    ) -> Result<Option<String>, Box<super::Error>> {
+        let mut tables = self.tables();
+        let thumbprint = record.key.thumbprint();
+
+        if let Some(existing) = tables.thumbprints.get(&thumbprint) {
+            return Ok(Some(existing.clone()));
+        }
+
+        tables.thumbprints.insert(thumbprint, id.to_string());
+        tables.accounts.insert(id.to_string(), record);
+
+        Ok(None)
+    }
+
+    fn update_account(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut Account) -> Result<(), Box<super::Error>>,
+    ) -> Result<Option<AccountRecord>, Box<super::Error>> {
+        self::update(&mut self.tables().accounts, id, &mut |record| {
+            update(&mut record.account)
+        })
+    }
+
+    fn replace_account_key(&self, id: &str, key: JsonWebKey) -> Result<bool, Box<super::Error>> {
+        let mut tables = self.tables();
+        let thumbprint = key.thumbprint();
+
+        if tables.thumbprints.contains_key(&thumbprint) {
+            return Ok(false);
+        }
+
+        let Some(record) = tables.accounts.get_mut(id) else {
+            return Err(Box::new(super::Error::new(
+                super::ErrorType::ServerInternal,
+                "account does not exist",
+            )));
+        };
+        let previous = std::mem::replace(&mut record.key, key).thumbprint();
+        tables.thumbprints.remove(&previous);
+        tables.thumbprints.insert(thumbprint, id.to_string());
+
+        Ok(true)
+    }
+
+    fn order(&self, id: &str) -> Result<Option<OrderRecord>, Box<super::Error>> {
+        Ok(self.tables().orders.get(id).cloned())
+    }
+
+    fn account_orders(&self, account: &str) -> Result<Vec<String>, Box<super::Error>> {
+        Ok(self
+            .tables()
+            .account_orders
+            .get(account)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn insert_order(&self, id: &str, record: OrderRecord) -> Result<(), Box<super::Error>> {
+        let mut tables = self.tables();
+        let account = record.account.clone();
+
+        if tables.orders.insert(id.to_string(), record).is_none() {
+            tables
+                .account_orders
+                .entry(account)
+                .or_default()
+                .push(id.to_string());
+        }
+
+        Ok(())
+    }
+
+    fn update_order(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut OrderRecord) -> Result<(), Box<super::Error>>,
+    ) -> Result<Option<OrderRecord>, Box<super::Error>> {
+        self::update(&mut self.tables().orders, id, update)
+    }
+
+    fn authorization(&self, id: &str) -> Result<Option<AuthorizationRecord>, Box<super::Error>> {
+        Ok(self.tables().authorizations.get(id).cloned())
+    }
+
+    fn insert_authorization(
+        &self,
+        id: &str,
+        record: AuthorizationRecord,
+    ) -> Result<(), Box<super::Error>> {
+        self.tables().authorizations.insert(id.to_string(), record);
+
+        Ok(())
+    }
+
+    fn update_authorization(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut AuthorizationRecord) -> Result<(), Box<super::Error>>,
+    ) -> Result<Option<AuthorizationRecord>, Box<super::Error>> {
+        self::update(&mut self.tables().authorizations, id, update)
+    }
+
+    fn challenge(&self, id: &str) -> Result<Option<ChallengeRecord>, Box<super::Error>> {
+        Ok(self.tables().challenges.get(id).cloned())
+    }
+
+    fn insert_challenge(&self, id: &str, record: ChallengeRecord) -> Result<(), Box<super::Error>> {
+        self.tables().challenges.insert(id.to_string(), record);
+
+        Ok(())
+    }
+
+    fn update_challenge(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut ChallengeRecord) -> Result<(), Box<super::Error>>,
+    ) -> Result<Option<ChallengeRecord>, Box<super::Error>> {
+        self::update(&mut self.tables().challenges, id, update)
+    }
+
+    fn certificate(&self, id: &str) -> Result<Option<CertificateRecord>, Box<super::Error>> {
+        Ok(self.tables().certificates.get(id).cloned())
+    }
+
+    fn certificate_by_leaf(&self, leaf: &[u8]) -> Result<Option<String>, Box<super::Error>> {
+        Ok(self.tables().leaves.get(leaf).cloned())
+    }
+
+    fn insert_certificate(
+        &self,
+        id: &str,
+        record: CertificateRecord,
+    ) -> Result<(), Box<super::Error>> {
+        let mut tables = self.tables();
+        if let Some(leaf) = leaf(&record.chain) {
+            tables.leaves.insert(leaf, id.to_string());
+        }
+        tables.certificates.insert(id.to_string(), record);
+
+        Ok(())
+    }
+
+    fn update_certificate(
+        &self,
+        id: &str,
+        update: &mut dyn FnMut(&mut CertificateRecord) -> Result<(), Box<super::Error>>,
+    ) -> Result<Option<CertificateRecord>, Box<super::Error>> {
+        let mut tables = self.tables();
+        let chain = tables
+            .certificates
+            .get(id)
+            .map(|record| record.chain.clone());
+        let updated = self::update(&mut tables.certificates, id, update)?;
+
+        match (chain, &updated) {
+            (Some(chain), Some(record)) if chain != record.chain => {
+                if let Some(leaf) = leaf(&chain) {
+                    tables.leaves.remove(&leaf);
+                }
+                if let Some(leaf) = leaf(&record.chain) {
+                    tables.leaves.insert(leaf, id.to_string());
+                }
+            }
+            _ => (),
+        }
+
+        Ok(updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2::{AccountOrdersUrl, AccountStatus, Error, ErrorType, JwsAlgorithm, SigningKey};
+
+    fn account_key() -> JsonWebKey {
+        let pkcs8 = SigningKey::generate_pkcs8(&JwsAlgorithm::Es256).unwrap();
+        SigningKey::from_pkcs8(JwsAlgorithm::Es256, &pkcs8)
+            .unwrap()
+            .public_key()
+    }
+
+    fn account(key: &JsonWebKey) -> AccountRecord {
+        AccountRecord {
+            key: key.clone(),
+            account: Account {
+                status: AccountStatus::Valid,
+                contact: None,
+                terms_of_service_agreed: None,
+                external_account_binding: None,
+                orders: AccountOrdersUrl::new("https://example.com/acme/orders"),
+            },
+        }
+    }
+
+    fn order(account: &str) -> OrderRecord {
+        OrderRecord {
+            account: account.to_string(),
+            order: Order::from_str(
+                r#"{
+                    "status": "pending",
+                    "identifiers": [{"type": "dns", "value": "example.com"}],
+                    "authorizations": [],
+                    "finalize": "https://example.com/acme/order/1/finalize"
+                }"#,
+            )
+            .unwrap(),
+            authorizations: vec![],
+            certificate_signing_request: None,
+        }
+    }
+
+    #[test]
+    fn account_keys() {
+        let store = MemoryStore::new();
+        let key = account_key();
+        let other_key = account_key();
+
+        assert_eq!(store.insert_account("1", account(&key)).unwrap(), None);
+        assert_eq!(
+            store.insert_account("2", account(&key)).unwrap(),
+            Some("1".to_string())
+        );
+        assert!(store.account("2").unwrap().is_none());
+
+        assert_eq!(
+            store.insert_account("2", account(&other_key)).unwrap(),
+            None
+        );
+        assert!(!store.replace_account_key("1", other_key.clone()).unwrap());
+        assert!(!store.replace_account_key("1", key.clone()).unwrap());
+
+        let new_key = account_key();
+        assert!(store.replace_account_key("1", new_key.clone()).unwrap());
+        assert_eq!(store.account("1").unwrap().unwrap().key, new_key);
+        assert_eq!(
+            store.account_by_thumbprint(&new_key.thumbprint()).unwrap(),
+            Some("1".to_string())
+        );
+        assert_eq!(
+            store.account_by_thumbprint(&key.thumbprint()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn failed_update() {
+        let store = MemoryStore::new();
+        store.insert_account("1", account(&account_key())).unwrap();
+
+        let error = store
+            .update_account("1", &mut |account| {
+                account.status = AccountStatus::Deactivated;
+                Err(Box::new(Error::new(ErrorType::Malformed, "rejected")))
+            })
+            .unwrap_err();
+
+        assert_eq!(error.type_, ErrorType::Malformed);
+        assert_eq!(
+            store.account("1").unwrap().unwrap().account.status,
+            AccountStatus::Valid
+        );
+
+        let updated = store
+            .update_account("1", &mut |account| {
+                account.status = AccountStatus::Deactivated;
+                Ok(())
+            })
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(updated.account.status, AccountStatus::Deactivated);
+        assert_eq!(
+            store.account("1").unwrap().unwrap().account.status,
+            AccountStatus::Deactivated
+        );
+        assert!(store
+            .update_account("2", &mut |_| Ok(()))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn account_orders() {
+        let store = MemoryStore::new();
+        for (id, account) in [("c", "1"), ("a", "2"), ("b", "1")] {
+            store.insert_order(id, order(account)).unwrap();
+        }
+        store.update_order("c", &mut |_| Ok(())).unwrap();
+
+        assert_eq!(store.account_orders("1").unwrap(), vec!["c", "b"]);
+        assert_eq!(store.account_orders("2").unwrap(), vec!["a"]);
+        assert!(store.account_orders("3").unwrap().is_empty());
+    }
+
+    #[test]
+    fn certificates_by_leaf() {
+        let store = MemoryStore::new();
+        let chain = include_str!("testdata/leaf.pem").to_string();
+        let leaf = CertificateChain::from_pem(&chain).unwrap().leaf().to_vec();
+
+        store
+            .insert_certificate(
+                "1",
+                CertificateRecord {
+                    account: "1".to_string(),
+                    chain: chain.clone(),
+                    revoked: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(
+            store.certificate_by_leaf(&leaf).unwrap(),
+            Some("1".to_string())
+        );
+        assert_eq!(store.certificate_by_leaf(b"other").unwrap(), None);
+
+        store
+            .update_certificate("1", &mut |record| {
+                record.revoked = Some(CertificateRevocationReason::KeyCompromise);
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(
+            store.certificate_by_leaf(&leaf).unwrap(),
+            Some("1".to_string())
+        );
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+/// Defines the RFC 3339 timestamp used by ACME resources (e.g. `expires`, `notBefore`,
+/// `notAfter`, `validated`).
+///
+/// Timestamps hold the string sent over the wire whichever features are enabled, so that enabling
+/// the `time` feature does not change any public field type. With the `time` feature enabled,
+/// they can be created from and parsed into `time::OffsetDateTime` values.
+///
+/// For more information, refer to [RFC 8555 § 7.1](https://datatracker.ietf.org/doc/html/rfc8555#section-7.1)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json", serde(transparent))]
+pub struct Timestamp(String);
+
+impl Timestamp {
+    /// Creates a timestamp from its RFC 3339 representation, which is not validated
+    pub fn new(timestamp: impl Into<String>) -> Timestamp {
+        Timestamp(timestamp.into())
+    }
+
+    /// Returns the timestamp as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature = "time")]
+impl Timestamp {
+    /// Parses the timestamp as an RFC 3339 date-time, returning `None` if it is invalid
+    pub fn to_date_time(&self) -> Option<time::OffsetDateTime> {
+        time::OffsetDateTime::parse(&self.0, &time::format_description::well_known::Rfc3339).ok()
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for Timestamp {
+    /// Formats the date-time in UTC
+    ///
+    /// RFC 3339 only covers the years 0 to 9999; date-times outside of this range are formatted
+    /// using `time::OffsetDateTime`'s `Display` implementation and cannot be parsed back.
+    fn from(date: time::OffsetDateTime) -> Timestamp {
+        let date = date.to_offset(time::UtcOffset::UTC);
+
+        Timestamp(
+            date.format(&time::format_description::well_known::Rfc3339)
+                .unwrap_or_else(|_| date.to_string()),
+        )
+    }
+}
+
+impl From<String> for Timestamp {
+    fn from(timestamp: String) -> Timestamp {
+        Timestamp(timestamp)
+    }
+}
+
+impl AsRef<str> for Timestamp {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(all(test, feature = "time"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_time() {
+        let timestamp = Timestamp::new("2016-01-05T14:09:07.99+01:00");
+        let date = timestamp.to_date_time().unwrap();
+        assert_eq!(date.unix_timestamp(), 1451999347);
+
+        let timestamp = Timestamp::from(date);
+        assert_eq!(timestamp.as_str(), "2016-01-05T13:09:07.99Z");
+        assert_eq!(timestamp.to_date_time(), Some(date));
+
+        for timestamp in [
+            "",
+            "2016-01-05",
+            "2016-01-05T14:09:07",
+            "2016-13-05T14:09:07Z",
+        ] {
+            assert_eq!(Timestamp::new(timestamp).to_date_time(), None);
+        }
+    }
+}